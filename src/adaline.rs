@@ -0,0 +1,249 @@
+use std::num::NonZeroU64;
+
+use num_rational::Ratio;
+
+use crate::{
+    perceptron::{na, PerFloat, PerVec},
+    util::{BoolExpect, BoolExpectation, Correctness, SampleOutcome},
+    LinearNeuron, Neuron,
+};
+
+/// Results of one training epoch of [`Adaline`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EpochReport {
+    /// Share of samples decided correctly before learning from them.
+    pub accuracy: Ratio<u64>,
+    /// Mean squared error of the linear output before learning from each sample. `None` if no sample had an expectation.
+    pub mse: Option<PerFloat>,
+}
+
+/// ADAptive LInear NEuron.
+///
+/// Decides like [`crate::Perceptron`], but learns from the continuous error of its linear output (least mean squares rule).
+/// Expectations `true` and `false` are targets `1` and `-1` for `w·x - theta`.
+/// Unlike perceptron, it updates on every sample, minimizing the mean squared error.
+/// Accuracy and multi-sample training come from [`Neuron`].
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Adaline<const D: usize> {
+    wages: PerVec<D>,
+    theta: PerFloat,
+    /// Learning rate.
+    #[serde(default = "default_alpha")]
+    alpha: PerFloat,
+}
+
+fn default_alpha() -> PerFloat {
+    Adaline::<0>::ALPHA
+}
+
+impl<const D: usize> Default for Adaline<D> {
+    fn default() -> Self {
+        Self {
+            wages: na::SVector::zeros(),
+            theta: 0.,
+            alpha: Self::ALPHA,
+        }
+    }
+}
+impl<const D: usize> Adaline<D> {
+    /// Default learning rate.
+    pub const ALPHA: PerFloat = 0.01;
+
+    // CRUD-C: Constructors
+
+    /// Sets the learning rate, [`Self::ALPHA`] by default.
+    pub fn with_alpha(mut self, alpha: PerFloat) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    // CRUD-R: Properties
+
+    pub fn alpha(&self) -> PerFloat {
+        self.alpha
+    }
+
+    /// Activation function. Takes the linear output.
+    pub fn activation(&self, net_input: PerFloat) -> bool {
+        net_input >= 0.
+    }
+    /// Returns mean squared error of the linear output for the given test data.
+    ///
+    /// Samples without expectation are skipped. Returns `None` if none is left.
+    pub fn mse_for<'i, II, EI, E>(&self, inputs: II, expectations: EI) -> Option<PerFloat>
+    where
+        II: IntoIterator<Item = &'i PerVec<D>>,
+        EI: IntoIterator<Item = E>,
+        E: BoolExpect,
+    {
+        let [mut squared_errors, mut all] = [0., 0.];
+        for (input, expectation) in inputs.into_iter().zip(expectations) {
+            if let Some(error) = self.error_for(input, expectation) {
+                squared_errors += error * error;
+                all += 1.;
+            }
+        }
+        (all != 0.).then(|| squared_errors / all)
+    }
+    /// Returns `target - net_input`, if there is a target.
+    fn error_for<E: BoolExpect>(&self, input: &PerVec<D>, expectation: E) -> Option<PerFloat> {
+        let BoolExpectation::Expect(expectation) = expectation.expectation() else {
+            return None;
+        };
+        let target = if expectation { 1. } else { -1. };
+        Some(target - self.net_input(input))
+    }
+
+    // CRUD-U: Training [`self`].
+
+    /// Like [`Neuron::train_on_weighted_sample`], but also returns the error before learning.
+    fn learn_from_sample<E>(
+        &mut self,
+        input: &PerVec<D>,
+        expectation: E,
+        weight: PerFloat,
+    ) -> (SampleOutcome, Option<PerFloat>)
+    where
+        E: BoolExpect,
+    {
+        let correctness = if expectation.is_met_by(self.decide_for(input)) {
+            Correctness::Correct
+        } else {
+            Correctness::Incorrect
+        };
+        let Some(error) = self.error_for(input, expectation) else {
+            // No expectation ==> nothing to do ==> everything is ok
+            return (SampleOutcome::new(Correctness::Correct, false), None);
+        };
+        // Gradient step on the squared error.
+        let translation_multiplier = self.alpha * error * weight;
+        crate::util::sf32_vec::add_assign(&mut self.wages, &(translation_multiplier * input));
+        self.theta -= translation_multiplier; // Input is -1.
+        let outcome = SampleOutcome::new(correctness, translation_multiplier != 0.);
+        (outcome, Some(error))
+    }
+
+    /// Trains on every sample once.
+    ///
+    /// Returns `None` for empty data.
+    pub fn train_epoch<'i, II, EI, E>(&mut self, inputs: II, expecteds: EI) -> Option<EpochReport>
+    where
+        II: IntoIterator<Item = &'i PerVec<D>>,
+        EI: IntoIterator<Item = E>,
+        E: BoolExpect,
+    {
+        let [mut correct, mut all] = [0, 0];
+        let [mut squared_errors, mut with_target] = [0., 0.];
+        for (input, expected) in inputs.into_iter().zip(expecteds) {
+            let (outcome, error) = self.learn_from_sample(input, expected, 1.);
+            if outcome.is_correct() {
+                correct += 1;
+            }
+            all += 1;
+            if let Some(error) = error {
+                squared_errors += error * error;
+                with_target += 1.;
+            }
+        }
+        Some(EpochReport {
+            accuracy: Ratio::new(correct, NonZeroU64::new(all)?.get()),
+            mse: (with_target != 0.).then(|| squared_errors / with_target),
+        })
+    }
+
+    /// Trains for exactly `epochs` epochs. Returns report of each epoch.
+    pub fn fit_epochs<'i, II, EI, E>(
+        &mut self,
+        inputs: II,
+        expecteds: EI,
+        epochs: usize,
+    ) -> Vec<EpochReport>
+    where
+        II: Copy + IntoIterator<Item = &'i PerVec<D>>,
+        EI: Copy + IntoIterator<Item = E>,
+        E: BoolExpect,
+    {
+        (0..epochs)
+            .map_while(|_| self.train_epoch(inputs, expecteds))
+            .collect()
+    }
+}
+
+impl<const D: usize> Neuron<PerVec<D>> for Adaline<D> {
+    fn decide_for(&self, input: &PerVec<D>) -> bool {
+        self.activation(self.net_input(input))
+    }
+    fn train_on_weighted_sample<E: BoolExpect>(
+        &mut self,
+        input: &PerVec<D>,
+        expectation: E,
+        weight: PerFloat,
+    ) -> SampleOutcome {
+        self.learn_from_sample(input, expectation, weight).0
+    }
+    fn regularize(&mut self, regularization: &crate::Regularization) {
+        regularization.apply(&mut self.wages, self.alpha);
+    }
+    fn penalty(&self, regularization: &crate::Regularization) -> PerFloat {
+        regularization.penalty(&self.wages)
+    }
+}
+impl<const D: usize> LinearNeuron<D> for Adaline<D> {
+    fn wages(&self) -> &PerVec<D> {
+        &self.wages
+    }
+    fn theta(&self) -> PerFloat {
+        self.theta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_by_the_error_even_when_deciding_right() {
+        let mut adaline = Adaline::<2>::default();
+        let input = PerVec::<2>::new(1., 2.);
+        // Net input 0 decides `true` already, but falls short of target 1.
+        assert_eq!(
+            adaline.train_on_weighted_sample(&input, true, 2.),
+            SampleOutcome::new(Correctness::Correct, true)
+        );
+        assert_eq!(adaline.wages(), &PerVec::<2>::new(0.02, 0.04));
+        assert_eq!(adaline.theta(), -0.02);
+
+        let mut adaline = Adaline::<2>::default().with_alpha(0.1);
+        adaline.train_on_weighted_sample(&input, true, 1.);
+        assert_eq!(adaline.wages(), &PerVec::<2>::new(0.1, 0.2));
+    }
+
+    #[test]
+    fn samples_without_expectation_are_skipped() {
+        let mut adaline = Adaline::<1>::default();
+        let inputs = [PerVec::<1>::new(1.)];
+        let expectations = [BoolExpectation::NoExpect];
+        assert_eq!(adaline.mse_for(&inputs, expectations), None);
+        let report = adaline.train_epoch(&inputs, expectations).unwrap();
+        assert_eq!(report.accuracy, Ratio::from_integer(1));
+        assert_eq!(report.mse, None);
+        assert_eq!(adaline.wages(), &PerVec::<1>::zeros());
+        assert_eq!(adaline.train_epoch(&[], expectations), None);
+    }
+
+    #[test]
+    fn epochs_reduce_mean_squared_error() {
+        let inputs = [-2., -1., 1., 2.].map(PerVec::<1>::new);
+        let expecteds = [false, false, true, true];
+        let mut adaline = Adaline::<1>::default();
+        let initial_mse = adaline.mse_for(&inputs, expecteds).unwrap();
+        let reports = adaline.fit_epochs(&inputs, expecteds, 50);
+        assert_eq!(reports.len(), 50);
+        assert!(reports.windows(2).all(|pair| pair[1].mse <= pair[0].mse));
+        assert!(adaline.mse_for(&inputs, expecteds).unwrap() < initial_mse);
+        assert_eq!(
+            adaline.accuracy_for(&inputs, expecteds),
+            Some(Ratio::from_integer(1))
+        );
+    }
+}
@@ -1,29 +1,182 @@
 pub mod args {
     //! Facilitates usage of this app's arguments.
 
+    use std::path::PathBuf;
+
     use ic::util;
 
     #[derive(clap::Parser, Debug)]
     #[command(version, about, long_about = ic::executable_desc!())]
     pub struct AppArgs {
-        /// Delimiter used for provided floating point values.
+        /// TOML or YAML file configuring training, chosen by `.toml`, `.yaml` or `.yml` extension.
+        ///
+        /// Its sections are `data`, `preprocessing`, `network`, `algorithm` and `stopping`. Options below override its values.
+        #[arg(short, long)]
+        pub config: Option<PathBuf>,
+
+        /// Delimiter used for provided floating point values. `,` by default.
         ///
         /// Iris data should be provided in CSV format with separator being optionally overwriten by this option.
-        #[arg(short, long, default_value_t = util::AsciiChar7Bit::COMMA)]
-        pub separator: util::AsciiChar7Bit,
+        #[arg(short, long)]
+        pub separator: Option<util::AsciiChar7Bit>,
 
-        /// Measures this classifier's accuracy using testing irises data.
-        #[arg(short = 'a', long, default_value_t = true)]
+        /// Measures this classifier's accuracy using testing irises data. `-a=false` skips it.
+        #[arg(short = 'a', long, default_value_t = true, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = clap::ArgAction::Set)]
         pub run_accuracy_measure: bool,
+
+        /// File with classified irises used to train the classifier. `-` reads stdin. `./data/training_irises.csv` by default.
+        ///
+        /// Files with `.arff` extension are read as ARFF with species given by nominal names, e.g. "Iris-setosa".
+        /// Other files and stdin are read as headerless CSV with species codes.
+        /// CSV labels can also be `?` for an unknown species, or `|`-separated candidates, e.g. `1|2`. ARFF classes can be `?` too.
+        /// Label `!`, also as ARFF class, marks an iris of none of the species. Such irises train the reject output, classified as `null`.
+        #[arg(short, long)]
+        pub train: Option<PathBuf>,
+
+        /// File with classified irises used to measure accuracy. `-` reads stdin. `./data/testing_irises.csv` by default.
+        ///
+        /// Formats are as for `--train`.
+        #[arg(long)]
+        pub test: Option<PathBuf>,
+
+        /// Seed of the shuffle of training irises, making training reproducible.
+        #[arg(long)]
+        pub seed: Option<u64>,
+
+        /// Step size of perceptron weight updates. 0.1 by default.
+        #[arg(long)]
+        pub learning_rate: Option<f32>,
+
+        /// Stops training after this many epochs.
+        #[arg(long)]
+        pub max_epochs: Option<u64>,
+
+        /// Stops training after more than this many epochs in a row without accuracy progress. 10 by default, unless epochs or time are limited.
+        #[arg(long)]
+        pub patience: Option<u64>,
+
+        /// File with irises to classify, as headerless CSV. `-` reads stdin.
+        #[arg(short, long, default_value = crate::read::STD_STREAM_PATH)]
+        pub input: PathBuf,
+
+        /// File to write classifications to. `-` writes stdout.
+        #[arg(short, long, default_value = crate::read::STD_STREAM_PATH)]
+        pub output: PathBuf,
+
+        /// Minimal confidence needed to classify an iris. Less confident irises are reported as "uncertain".
+        ///
+        /// Confidence is the distance of an iris from decision boundaries of perceptrons that classified it.
+        #[arg(short, long)]
+        pub reject_threshold: Option<f32>,
+
+        /// Prints coverage and accuracy on testing irises for every distinct reject threshold to stderr.
+        #[arg(long)]
+        pub reject_curve: bool,
+
+        /// Format of printed classifications.
+        #[arg(short, long, value_enum, default_value_t)]
+        pub format: crate::output::OutputFormat,
+
+        /// Adds a score of each species to printed classifications. Only the chosen species scores non-negative.
+        #[arg(long)]
+        pub scores: bool,
+
+        /// Adds position of each iris among input irises, starting at 0, to printed classifications.
+        #[arg(long)]
+        pub row_index: bool,
+
+        /// Saves the trained network as JSON, e.g. for `serve`.
+        #[arg(long)]
+        pub save_model: Option<PathBuf>,
+
+        /// Logs more to stderr: `-v` data loading and training summaries, `-vv` every epoch, `-vvv` every weight update.
+        ///
+        /// `RUST_LOG` environment variable, e.g. `RUST_LOG=perc_ic::stopping=debug`, takes precedence for the targets it names.
+        #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+        pub verbose: u8,
+
+        /// Logs less: `-q` errors only, `-qq` nothing. Warnings are logged by default.
+        #[arg(short, long, action = clap::ArgAction::Count)]
+        pub quiet: u8,
+
+        /// Logs JSON objects, one per line, instead of text.
+        #[arg(long)]
+        pub log_json: bool,
+
+        /// What to do. Classifies irises from stdin if omitted.
+        #[command(subcommand)]
+        pub command: Option<Command>,
+    }
+
+    #[derive(clap::Subcommand, Debug)]
+    pub enum Command {
+        /// Classifies irises given on stdin. The default.
+        Classify,
+        /// Draws decision boundaries of the trained network over the training irises.
+        Plot(PlotArgs),
+        /// Serves a network saved with `--save-model` over HTTP, instead of training one.
+        ///
+        /// `POST /predict` classifies irises given as JSON, `GET /model` describes the network and `GET /health` checks the server.
+        /// Irises less confident than `--reject-threshold` are classified as `null`.
+        Serve(ServeArgs),
+        /// Reads commands from stdin to explore the trained network, adjust it and retrain it. Type `help` for commands.
+        Repl(ReplArgs),
+    }
+
+    #[derive(clap::Args, Debug)]
+    pub struct PlotArgs {
+        /// Image file to create. Its extension, `.svg` or `.png`, chooses the format.
+        pub output: PathBuf,
+
+        /// Iris parameter on the horizontal axis: 0 sepal length, 1 sepal width, 2 petal length, 3 petal width.
+        #[arg(short, default_value_t = 2)]
+        pub x: usize,
+
+        /// Iris parameter on the vertical axis. See `-x`.
+        #[arg(short, default_value_t = 3)]
+        pub y: usize,
+
+        /// Width of the image in pixels.
+        #[arg(long, default_value_t = 600)]
+        pub width: u32,
+
+        /// Height of the image in pixels.
+        #[arg(long, default_value_t = 600)]
+        pub height: u32,
+    }
+
+    #[derive(clap::Args, Debug)]
+    pub struct ServeArgs {
+        /// JSON file of the network, as written by `--save-model`.
+        pub model: PathBuf,
+
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        pub addr: String,
+
+        /// Number of requests answered concurrently.
+        #[arg(long, default_value_t = std::num::NonZeroUsize::new(4).unwrap())]
+        pub workers: std::num::NonZeroUsize,
+    }
+
+    #[derive(clap::Args, Debug)]
+    pub struct ReplArgs {
+        /// JSON file of a network written by `--save-model`, explored instead of a newly trained one.
+        ///
+        /// `retrain` then trains on learned irises only.
+        #[arg(long)]
+        pub model: Option<PathBuf>,
     }
 }
 pub mod cfg {
     //! Defines app's configuration.
 
     use core::panic;
-    use std::sync::OnceLock;
+    use std::{path::Path, sync::OnceLock};
 
-    use derive_more::{Constructor, Deref, DerefMut};
+    use derive_more::{Deref, DerefMut};
+
+    use crate::config::TrainingConfig;
 
     /// The only app configuration object.
     pub static APP_CFG: OnceLock<AppCfg> = OnceLock::new();
@@ -34,15 +187,218 @@ pub mod cfg {
     /// * If it hasn't been initialized.
     pub fn app_cfg() -> &'static AppCfg {
         if let Some(app_cfg) = APP_CFG.get() {
-            return app_cfg;
+            app_cfg
         } else {
             panic!("Logical error: app config used before being initialized.")
         }
     }
 
     /// App configuration.
-    #[derive(Constructor, Debug, Deref, DerefMut)]
+    #[derive(Debug, Deref, DerefMut)]
     pub struct AppCfg {
+        #[deref]
+        #[deref_mut]
         app_args: crate::AppArgs,
+        /// Training config read from `--config`, overridden by app arguments.
+        pub training: TrainingConfig,
+    }
+
+    impl AppCfg {
+        // CRUD-C: Constructors
+
+        /// Resolves training config of `app_args`.
+        ///
+        /// Returned error reports what failed, not only why.
+        pub fn new(app_args: crate::AppArgs) -> anyhow::Result<Self> {
+            let mut training = match &app_args.config {
+                Some(path) => TrainingConfig::read_path(path)?,
+                None => TrainingConfig::default(),
+            };
+            let data = &mut training.data;
+            override_with(&mut data.train, app_args.train.clone());
+            override_with(&mut data.test, app_args.test.clone());
+            override_with(&mut data.separator, app_args.separator);
+            override_with(&mut training.preprocessing.seed, app_args.seed.map(Some));
+            let algorithm = &mut training.algorithm;
+            override_with(&mut algorithm.learning_rate, app_args.learning_rate);
+            let stopping = &mut training.stopping;
+            override_with(&mut stopping.max_epochs, app_args.max_epochs.map(Some));
+            override_with(&mut stopping.patience, app_args.patience.map(Some));
+            training.validate()?;
+            Ok(Self { app_args, training })
+        }
+
+        // CRUD-R: Properties
+
+        /// Checks consistency of arguments that clap can't check.
+        ///
+        /// # Errors
+        /// * If more than one used input is stdin.
+        pub fn validate(&self) -> anyhow::Result<()> {
+            use crate::{
+                app::args::Command,
+                read::{is_std_stream, STD_STREAM_PATH},
+            };
+
+            let is_plotting = matches!(self.command, Some(Command::Plot(_)));
+            let is_serving = matches!(self.command, Some(Command::Serve(_)));
+            let (is_repl, is_model_loaded) = match &self.command {
+                Some(Command::Repl(repl_args)) => (true, repl_args.model.is_some()),
+                _ => (false, false),
+            };
+            let is_classifying = !is_plotting && !is_serving && !is_repl;
+            let is_testing = is_classifying && (self.run_accuracy_measure || self.reject_curve);
+            let data = &self.training.data;
+            let stdin_users: Vec<&str> = [
+                ("repl", is_repl, Path::new(STD_STREAM_PATH)),
+                ("--train", !is_serving && !is_model_loaded, &data.train),
+                ("--test", is_testing, &data.test),
+                ("--input", is_classifying, &self.input),
+            ]
+            .into_iter()
+            .filter(|&(_, is_used, path)| is_used && is_std_stream(path))
+            .map(|(name, _, _)| name)
+            .collect();
+            if stdin_users.len() > 1 {
+                anyhow::bail!(
+                    "Only one input can be read from stdin, but {} are all `-`.",
+                    stdin_users.join(", ")
+                );
+            }
+            Ok(())
+        }
+    }
+
+    /// Replaces `value` with `overriding` one, if any.
+    fn override_with<T>(value: &mut T, overriding: Option<T>) {
+        if let Some(overriding) = overriding {
+            *value = overriding;
+        }
+    }
+}
+
+/// Prints accuracy of `iris_classifier` on classified irises from `path`.
+///
+/// Returned error reports what failed, not only why.
+pub fn run_accuracy_measure<F>(iris_classifier: F, path: &std::path::Path) -> anyhow::Result<()>
+where
+    F: Fn(ic::UnclassifiedIris) -> ic::ClassifiedIris,
+{
+    let source = crate::read::describe_source(path);
+    let testing_irises = crate::read::classified_irises(path)?;
+    if testing_irises.is_empty() {
+        // It is not considered error, just a lack of measurement.
+        eprintln!("Classification accuracy for {source} couldn't be measured, due to it not containing any iris case.");
+        return Ok(());
+    }
+    let good_classifications_count = testing_irises
+        .iter()
+        .filter(|iris| iris_classifier(iris.parameters).classification == iris.classification)
+        .count();
+    eprintln!(
+        "Classification accuracy for {source} is {} .",
+        num_rational::Ratio::new(good_classifications_count, testing_irises.len())
+    );
+    Ok(())
+}
+
+/// Sets up logging to stderr as chosen by `-v`, `-q` and `--log-json`.
+///
+/// Returned error reports what failed, not only why.
+pub fn init_logging(app_args: &crate::AppArgs) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+
+    let level = match i16::from(app_args.verbose) - i16::from(app_args.quiet) {
+        ..=-2 => LevelFilter::OFF,
+        -1 => LevelFilter::ERROR,
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    let filter = EnvFilter::builder()
+        .with_default_directive(level.into())
+        .from_env()
+        .context("Failed to parse RUST_LOG.")?;
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    if app_args.log_json {
+        subscriber.json().try_init()
+    } else {
+        subscriber.try_init()
+    }
+    .map_err(|err| anyhow::anyhow!(err))
+    .context("Failed to set up logging.")
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::cfg::AppCfg;
+    use crate::AppArgs;
+
+    fn app_cfg(args: &[&str]) -> anyhow::Result<AppCfg> {
+        let args = std::iter::once("iris_classifier").chain(args.iter().copied());
+        AppCfg::new(AppArgs::try_parse_from(args)?)
+    }
+
+    #[test]
+    fn arguments_override_config_file() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("perc_ic-app-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[data]\ntest = \"file.csv\"\n[preprocessing]\nseed = 42\n[algorithm]\nlearning_rate = 0.05\n[stopping]\nmax_epochs = 500\n",
+        )?;
+        let path_arg = path.to_str().expect("Temp dir should be UTF-8.");
+        let from_file = app_cfg(&["--config", path_arg]);
+        let overridden = app_cfg(&[
+            "--config",
+            path_arg,
+            "--seed",
+            "7",
+            "--max-epochs",
+            "3",
+            "--test",
+            "arg.csv",
+        ]);
+        std::fs::remove_file(&path)?;
+
+        let from_file = from_file?.training;
+        assert_eq!(from_file.preprocessing.seed, Some(42));
+        assert_eq!(from_file.stopping.max_epochs, Some(500));
+
+        let overridden = overridden?.training;
+        assert_eq!(overridden.preprocessing.seed, Some(7));
+        assert_eq!(overridden.stopping.max_epochs, Some(3));
+        assert_eq!(overridden.data.test, std::path::Path::new("arg.csv"));
+        // Values without an argument stay as in the file.
+        assert_eq!(overridden.algorithm.learning_rate, 0.05);
+        assert_eq!(overridden.data.train, from_file.data.train);
+        Ok(())
+    }
+
+    #[test]
+    fn accuracy_flag_leaves_subcommand_alone() -> anyhow::Result<()> {
+        let args = AppArgs::try_parse_from(["iris_classifier", "-a", "plot", "plot.svg"])?;
+        assert!(args.run_accuracy_measure);
+        assert!(matches!(
+            args.command,
+            Some(crate::app::args::Command::Plot(_))
+        ));
+        let args = AppArgs::try_parse_from(["iris_classifier", "-a=false"])?;
+        assert!(!args.run_accuracy_measure);
+        Ok(())
+    }
+
+    #[test]
+    fn arguments_without_config_override_defaults() -> anyhow::Result<()> {
+        let cfg = app_cfg(&["--learning-rate", "0.2", "--patience", "3"])?;
+        assert_eq!(cfg.training.algorithm.learning_rate, 0.2);
+        assert_eq!(cfg.training.stopping.patience, Some(3));
+        assert!(app_cfg(&["--learning-rate", "0"]).is_err());
+        Ok(())
     }
 }
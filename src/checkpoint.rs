@@ -0,0 +1,192 @@
+//! Training of [`OneLayerNN`] that can be interrupted and resumed.
+
+use std::{path::Path, time::Instant};
+
+use anyhow::Context;
+use num_rational::Ratio;
+use num_traits::zero;
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    nn::one_layer::OutputInt, perceptron::PerVec, stopping::TrainingProgress, util::IntExpect,
+    Neuron, OneLayerNN, Perceptron, StoppingCriteria,
+};
+
+/// Complete state of a [`OneLayerNN::fit`]-like training run.
+///
+/// Samples are visited in an order shuffled anew every epoch.
+/// As the RNG state is a part of the checkpoint, a resumed run produces the same network as an uninterrupted one.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(bound(
+    serialize = "U: serde::Serialize",
+    deserialize = "U: serde::Deserialize<'de>"
+))]
+pub struct Checkpoint<const N: usize, const D: usize, U = Perceptron<D>> {
+    network: OneLayerNN<N, D, U>,
+    rng: ChaCha8Rng,
+    criteria: StoppingCriteria,
+    progress: TrainingProgress,
+}
+
+impl<const N: usize, const D: usize, U> Checkpoint<N, D, U> {
+    // CRUD-C: Constructors
+
+    /// Prepares training of `network` until `criteria` are met.
+    ///
+    /// Time spent before an interruption counts towards [`StoppingCriteria::max_duration`] after resuming.
+    pub fn new(network: OneLayerNN<N, D, U>, criteria: StoppingCriteria, seed: u64) -> Self {
+        Self {
+            network,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            criteria,
+            progress: TrainingProgress::new(Some(zero())),
+        }
+    }
+    /// Loads checkpoint saved during [`Self::run`].
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn resume(path: impl AsRef<Path>) -> anyhow::Result<Self>
+    where
+        U: serde::de::DeserializeOwned,
+    {
+        crate::persist::load(path).context("Failed to resume training.")
+    }
+
+    // CRUD-R: Properties
+
+    pub fn network(&self) -> &OneLayerNN<N, D, U> {
+        &self.network
+    }
+    pub fn progress(&self) -> &TrainingProgress {
+        &self.progress
+    }
+    /// Whether further [`Self::run`] would train no more.
+    pub fn is_finished(&self) -> bool {
+        self.criteria.is_met_by(&self.progress)
+    }
+
+    // CRUD-U: Setters
+
+    /// Replaces the limits of training, e.g. to train a finished run further.
+    pub fn set_criteria(&mut self, criteria: StoppingCriteria) {
+        self.criteria = criteria;
+    }
+
+    // CRUD-D: Consuming conversions
+
+    pub fn into_network(self) -> OneLayerNN<N, D, U> {
+        self.network
+    }
+}
+
+impl<const N: usize, const D: usize, U> Checkpoint<N, D, U>
+where
+    U: Neuron<PerVec<D>> + Send + Sync + serde::Serialize,
+{
+    // CRUD-U: Training
+
+    /// Trains until finished, saving the checkpoint to `path` every `save_every` epochs and once finished.
+    ///
+    /// A checkpoint is first written next to `path` and then renamed, so an interruption never leaves a half-written file.
+    /// Returns the best accuracy reached.
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn run<I, E>(
+        &mut self,
+        inputs: &[PerVec<D>],
+        expecteds: &[E],
+        save_every: u64,
+        path: impl AsRef<Path>,
+    ) -> anyhow::Result<Option<Ratio<u64>>>
+    where
+        I: OutputInt,
+        E: IntExpect<ProvidedInt = I> + Clone + Sync + Send,
+    {
+        let path = path.as_ref();
+        let mut order: Vec<usize> = (0..inputs.len().min(expecteds.len())).collect();
+        while !self.is_finished() {
+            let _span = tracing::debug_span!("epoch", epoch = self.progress.epochs() + 1).entered();
+            // Shuffling the identity each time keeps the order a function of the RNG state only.
+            order.sort_unstable();
+            order.shuffle(&mut self.rng);
+            let started = Instant::now();
+            let outcome = self.network.train_epoch_on::<_, _, I, E>(
+                order.iter().map(|&idx| &inputs[idx]),
+                order.iter().map(|&idx| expecteds[idx].clone()),
+            );
+            self.progress.record_epoch(&outcome, started.elapsed());
+            if self.progress.epochs() % save_every.max(1) == 0 || self.is_finished() {
+                self.save(path)?;
+            }
+        }
+        self.progress.log_stop(&self.criteria);
+        Ok(self.progress.best_score())
+    }
+
+    /// Returned error reports what failed, not only why.
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        crate::persist::save(self, &tmp_path)?;
+        std::fs::rename(&tmp_path, path).with_context(|| {
+            format!(
+                "Failed to move checkpoint from \"{}\" to \"{}\".",
+                tmp_path.display(),
+                path.display()
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// XOR-like data, which no network of perceptrons learns, so every epoch updates weights.
+    fn samples() -> (Vec<PerVec<2>>, Vec<u8>) {
+        let inputs = vec![
+            PerVec::<2>::new(0., 0.),
+            PerVec::<2>::new(0., 1.),
+            PerVec::<2>::new(1., 0.),
+            PerVec::<2>::new(1., 1.),
+            PerVec::<2>::new(0.5, 0.2),
+        ];
+        (inputs, vec![0b00, 0b11, 0b11, 0b01, 0b10])
+    }
+
+    #[test]
+    fn resumed_run_matches_uninterrupted_one() -> anyhow::Result<()> {
+        let (inputs, expecteds) = samples();
+        let path =
+            std::env::temp_dir().join(format!("perc_ic-checkpoint-{}.json", std::process::id()));
+        let seed = 7;
+
+        let mut first = Checkpoint::new(
+            OneLayerNN::<2, 2>::default(),
+            StoppingCriteria::with_max_epochs(3),
+            seed,
+        );
+        first.run(&inputs, &expecteds, 1, &path)?;
+        let mut resumed = Checkpoint::<2, 2>::resume(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(resumed.progress().epochs(), 3);
+        resumed.set_criteria(StoppingCriteria::with_max_epochs(3 + 4));
+        resumed.run(&inputs, &expecteds, 10, &path)?;
+        std::fs::remove_file(&path)?;
+
+        let mut uninterrupted = Checkpoint::new(
+            OneLayerNN::<2, 2>::default(),
+            StoppingCriteria::with_max_epochs(3 + 4),
+            seed,
+        );
+        uninterrupted.run(&inputs, &expecteds, 10, &path)?;
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(resumed.progress().epochs(), 7);
+        assert_eq!(
+            resumed.network().weights(),
+            uninterrupted.network().weights()
+        );
+        Ok(())
+    }
+}
@@ -4,13 +4,18 @@ use derive_more::From;
 #[derive(From, Clone, Copy, Debug)]
 pub struct ClassificationExpectation(ic::IrisSpecies);
 
+impl ClassificationExpectation {
+    /// Output no species uses, learned by [`crate::PartialLabel::Reject`] irises.
+    pub const REJECT_OUTPUT: u8 = 0b_11;
+}
+
 impl IntExpect for ClassificationExpectation {
     type ProvidedInt = u8;
     type BoolExpectation = crate::util::BoolExpectation;
     fn is_met_by(&self, value: Self::ProvidedInt) -> bool {
         use ic::IrisSpecies as S;
         match self.0 {
-            S::Setosa => [0b_10, 0b_11].contains(&value),
+            S::Setosa => 0b_10 == value,
             S::Versicolor => 0b_00 == value,
             S::Virginica => 0b_01 == value,
         }
@@ -19,10 +24,18 @@ impl IntExpect for ClassificationExpectation {
     fn bit_expectation(&self, idx: usize) -> Self::BoolExpectation {
         use ic::IrisSpecies as S;
         match self.0 {
-            S::Setosa => [BoolExpectation::NoExpect, BoolExpectation::Expect(true)][idx],
+            // Bit 0 stays off, as `0b_11` is the reject output.
+            S::Setosa => [
+                BoolExpectation::Expect(false),
+                BoolExpectation::Expect(true),
+            ][idx],
             S::Versicolor | S::Virginica => {
                 [(self.0 == S::Virginica).expectation(), false.expectation()][idx]
             }
         }
     }
+
+    fn reject_output() -> Self::ProvidedInt {
+        Self::REJECT_OUTPUT
+    }
 }
@@ -0,0 +1,400 @@
+//! Configuration of training runs, readable from TOML or YAML files.
+//!
+//! All fields are optional in files. Missing ones take defaults, which train just like the app does without a config file.
+//! Relative paths are relative to the working directory, not to the config file.
+//!
+//! ```toml
+//! [data]
+//! train = "data/training_irises.csv"
+//! separator = ";"
+//!
+//! [preprocessing]
+//! seed = 42
+//!
+//! [network.regularization]
+//! l2 = 0.001
+//!
+//! [algorithm]
+//! rule = { Margin = { margin = 0.5 } }
+//! learning_rate = 0.05
+//!
+//! [stopping]
+//! max_epochs = 500
+//! target_accuracy = 0.98
+//! ```
+//!
+//! In YAML, enums like `rule` are maps too, e.g. `rule: { Margin: { margin: 0.5 } }`.
+
+use std::{path::Path, path::PathBuf, time::Duration};
+
+use anyhow::{bail, ensure, Context};
+use ic::util::AsciiChar7Bit;
+use num_rational::Ratio;
+
+use crate::{
+    perceptron::{PerFloat, UpdateRule},
+    IrisNetwork, Perceptron, Regularization, StoppingCriteria,
+};
+
+/// Everything that decides how an iris network is trained.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrainingConfig {
+    pub data: DataConfig,
+    pub preprocessing: PreprocessingConfig,
+    pub network: NetworkConfig,
+    pub algorithm: AlgorithmConfig,
+    pub stopping: StoppingConfig,
+}
+
+/// Where iris data comes from. Paths can be `-` for stdin.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DataConfig {
+    /// Classified irises to train on.
+    pub train: PathBuf,
+    /// Classified irises to measure accuracy on.
+    pub test: PathBuf,
+    /// Delimiter of CSV data.
+    #[serde(with = "crate::util::serde_display")]
+    pub separator: AsciiChar7Bit,
+}
+
+/// What is done to training irises before training.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PreprocessingConfig {
+    /// Whether to shuffle training irises once before training.
+    pub shuffle: bool,
+    /// Seed of the shuffle, which makes training reproducible. A random one if missing.
+    pub seed: Option<u64>,
+}
+
+/// Shape of the network and penalties on its weights.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Number of perceptrons. Iris networks have exactly 2.
+    pub units: usize,
+    /// Number of inputs of each perceptron. Iris networks have exactly 4.
+    pub inputs: usize,
+    pub regularization: Regularization,
+}
+
+/// How perceptrons learn.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlgorithmConfig {
+    pub rule: UpdateRule,
+    pub learning_rate: PerFloat,
+}
+
+/// Limits of training, as in [`StoppingCriteria`]. All unset by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StoppingConfig {
+    pub max_epochs: Option<u64>,
+    pub max_duration_secs: Option<f64>,
+    pub max_updates: Option<u64>,
+    /// Share of training irises classified right, e.g. `0.95`.
+    pub target_accuracy: Option<f64>,
+    /// Epochs in a row without accuracy progress tolerated.
+    /// [`StoppingConfig::DEFAULT_PATIENCE`] if neither this nor max epochs or max duration is set.
+    pub patience: Option<u64>,
+}
+
+impl Default for DataConfig {
+    fn default() -> Self {
+        Self {
+            train: ic::PATH_TO_TRAINING_IRISES.into(),
+            test: ic::PATH_TO_TESTING_IRISES.into(),
+            separator: AsciiChar7Bit::COMMA,
+        }
+    }
+}
+
+impl Default for PreprocessingConfig {
+    fn default() -> Self {
+        Self {
+            shuffle: true,
+            seed: None,
+        }
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            units: IRIS_UNITS,
+            inputs: IRIS_INPUTS,
+            regularization: Regularization::NONE,
+        }
+    }
+}
+
+impl Default for AlgorithmConfig {
+    fn default() -> Self {
+        Self {
+            rule: UpdateRule::default(),
+            learning_rate: Perceptron::<IRIS_INPUTS>::ALPHA,
+        }
+    }
+}
+
+const IRIS_UNITS: usize = 2;
+const IRIS_INPUTS: usize = 4;
+/// Denominator of target accuracy converted to a ratio.
+const ACCURACY_PRECISION: u64 = 1_000_000;
+
+impl TrainingConfig {
+    // CRUD-C: Constructors
+
+    /// Reads config from file. Its extension, `.toml`, `.yaml` or `.yml`, chooses the format.
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn read_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        (|| -> anyhow::Result<Self> {
+            let extension = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+            let text = std::fs::read_to_string(path)?;
+            let config = match extension.as_deref() {
+                Some("toml") => toml::from_str(&text)?,
+                // Through JSON, so enums are maps like in TOML, rather than YAML tags.
+                Some("yaml" | "yml") => {
+                    serde_json::from_value(serde_yaml::from_str::<serde_json::Value>(&text)?)?
+                }
+                _ => bail!("Unknown config format. Expected `.toml`, `.yaml` or `.yml` extension."),
+            };
+            tracing::debug!(?config, "Read training config.");
+            Ok(config)
+        })()
+        .with_context(|| format!("Failed to read config from \"{}\".", path.display()))
+    }
+
+    // CRUD-R: Properties
+
+    /// Checks what the types don't.
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        (|| -> anyhow::Result<()> {
+            let network = &self.network;
+            ensure!(
+                network.units == IRIS_UNITS && network.inputs == IRIS_INPUTS,
+                "Iris network has {IRIS_UNITS} units of {IRIS_INPUTS} inputs, not {} units of {} inputs.",
+                network.units,
+                network.inputs
+            );
+            let learning_rate = self.algorithm.learning_rate;
+            ensure!(
+                learning_rate.is_finite() && learning_rate > 0.,
+                "Learning rate must be positive, but is {learning_rate}."
+            );
+            let stopping = &self.stopping;
+            if let Some(secs) = stopping.max_duration_secs {
+                ensure!(
+                    secs.is_finite() && secs >= 0.,
+                    "Max duration must be a non-negative number of seconds, but is {secs}."
+                );
+            }
+            if let Some(accuracy) = stopping.target_accuracy {
+                ensure!(
+                    (0. ..=1.).contains(&accuracy),
+                    "Target accuracy must be between 0 and 1, but is {accuracy}."
+                );
+            }
+            stopping.criteria()?;
+            Ok(())
+        })()
+        .context("Invalid training config.")
+    }
+    /// Untrained network shaped and set up by the config.
+    pub fn network(&self) -> IrisNetwork {
+        let perceptron = Perceptron::default()
+            .with_rule(self.algorithm.rule)
+            .with_alpha(self.algorithm.learning_rate);
+        IrisNetwork::from_neurons(std::array::from_fn(|_| perceptron.clone()))
+            .with_regularization(self.network.regularization)
+    }
+}
+
+impl StoppingConfig {
+    /// Patience used when nothing else ends training runs.
+    pub const DEFAULT_PATIENCE: u64 = 10;
+
+    // CRUD-R: Properties
+
+    /// # Errors
+    /// * If max duration is no valid duration.
+    pub fn criteria(&self) -> anyhow::Result<StoppingCriteria> {
+        let max_duration = self
+            .max_duration_secs
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .context("Invalid max duration.")?;
+        let patience = match (self.max_epochs, max_duration, self.patience) {
+            (None, None, None) => Some(Self::DEFAULT_PATIENCE),
+            _ => self.patience,
+        };
+        let mut criteria = StoppingCriteria::new(self.max_epochs, max_duration, patience)?;
+        if let Some(max_updates) = self.max_updates {
+            criteria = criteria.max_updates(max_updates);
+        }
+        if let Some(accuracy) = self.target_accuracy {
+            let numer = (accuracy * ACCURACY_PRECISION as f64).round() as u64;
+            criteria = criteria.target_accuracy(Ratio::new(numer, ACCURACY_PRECISION));
+        }
+        Ok(criteria)
+    }
+}
+
+/// Network saved together with the config that trained it, e.g. by `--save-model`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct TrainedModel {
+    pub network: IrisNetwork,
+    pub config: TrainingConfig,
+}
+
+impl TrainedModel {
+    /// Returned error reports what failed, not only why.
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        crate::persist::save(self, path)
+    }
+    /// Loads model saved with [`Self::save`].
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        crate::persist::load(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stopping::EpochOutcome;
+
+    const TOML: &str = r#"
+[data]
+train = "data/training_irises.csv"
+separator = ";"
+
+[preprocessing]
+seed = 42
+
+[network.regularization]
+l2 = 0.001
+
+[algorithm]
+rule = { Margin = { margin = 0.5 } }
+learning_rate = 0.05
+
+[stopping]
+max_epochs = 500
+target_accuracy = 0.98
+"#;
+
+    const YAML: &str = r#"
+data:
+  train: data/training_irises.csv
+  separator: ";"
+preprocessing:
+  seed: 42
+network:
+  regularization:
+    l2: 0.001
+algorithm:
+  rule: { Margin: { margin: 0.5 } }
+  learning_rate: 0.05
+stopping:
+  max_epochs: 500
+  target_accuracy: 0.98
+"#;
+
+    /// Reads `text` as a config file with `extension`.
+    fn read_text(text: &str, extension: &str) -> anyhow::Result<TrainingConfig> {
+        let path = std::env::temp_dir().join(format!(
+            "perc_ic-config-{}-{extension}.{extension}",
+            std::process::id()
+        ));
+        std::fs::write(&path, text)?;
+        let config = TrainingConfig::read_path(&path);
+        std::fs::remove_file(&path)?;
+        config
+    }
+
+    #[test]
+    fn toml_and_yaml_read_the_same_config() -> anyhow::Result<()> {
+        let config = read_text(TOML, "toml")?;
+        assert_eq!(read_text(YAML, "yaml")?, config);
+        assert_eq!(config.data.train, Path::new("data/training_irises.csv"));
+        assert_eq!(config.data.test, DataConfig::default().test);
+        assert_eq!(config.preprocessing.seed, Some(42));
+        assert!(config.preprocessing.shuffle);
+        assert_eq!(config.network.regularization, Regularization::l2(0.001));
+        assert_eq!(config.algorithm.rule, UpdateRule::Margin { margin: 0.5 });
+        assert_eq!(config.stopping.max_epochs, Some(500));
+        assert_eq!(config.stopping.patience, None);
+        config.validate()
+    }
+
+    #[test]
+    fn patience_defaults_only_when_nothing_else_ends_training() -> anyhow::Result<()> {
+        let config = read_text("[stopping]\nmax_epochs = 3\n", "toml")?;
+        // Scores never improve after the first epoch, so patience would end runs.
+        let always_stuck = || EpochOutcome {
+            score: Some(Ratio::new(0, 1)),
+            ..EpochOutcome::default()
+        };
+        let epochs = config.stopping.criteria()?.run_recorded(None, always_stuck);
+        assert_eq!(epochs.records().len(), 3);
+        let epochs = TrainingConfig::default()
+            .stopping
+            .criteria()?
+            .run_recorded(None, always_stuck);
+        assert_eq!(
+            epochs.records().len() as u64,
+            StoppingConfig::DEFAULT_PATIENCE + 2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn empty_file_reads_default_config() -> anyhow::Result<()> {
+        assert_eq!(read_text("", "toml")?, TrainingConfig::default());
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_fields_and_formats_are_errors() {
+        assert!(read_text("[data]\ntrian = \"x.csv\"\n", "toml").is_err());
+        assert!(read_text("stoping: {}\n", "yml").is_err());
+        assert!(read_text(TOML, "json").is_err());
+    }
+
+    #[test]
+    fn invalid_values_fail_validation() {
+        let invalidate: [fn(&mut TrainingConfig); 4] = [
+            |config| config.network.units = 3,
+            |config| config.algorithm.learning_rate = 0.,
+            |config| config.stopping.max_duration_secs = Some(-1.),
+            |config| config.stopping.target_accuracy = Some(1.5),
+        ];
+        for (idx, invalidate) in invalidate.into_iter().enumerate() {
+            let mut config = TrainingConfig::default();
+            invalidate(&mut config);
+            assert!(config.validate().is_err(), "{idx}");
+        }
+    }
+
+    #[test]
+    fn network_is_set_up_by_config() {
+        let mut config = TrainingConfig::default();
+        config.algorithm.learning_rate = 0.5;
+        config.network.regularization = Regularization::l1(0.1);
+        let network = config.network();
+        assert_eq!(network.regularization(), Regularization::l1(0.1));
+        assert!(network.neurons().iter().all(|unit| unit.alpha() == 0.5));
+    }
+}
@@ -0,0 +1,136 @@
+//! Datasets used to train and evaluate [`crate::Perceptron`] and [`crate::OneLayerNN`].
+
+use std::{collections::HashMap, hash::Hash};
+
+use crate::perceptron::{PerFloat, PerVec};
+
+pub mod arff;
+pub mod libsvm;
+pub mod multi_label_csv;
+
+pub use partial_label::PartialLabel;
+pub mod partial_label;
+
+/// Input vectors paired with their labels.
+///
+/// Slices returned by [`Dataset::inputs`] and [`Dataset::labels`] can be passed straight to training methods.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dataset<const D: usize, L> {
+    // Invariant: Both have the same length.
+    inputs: Vec<PerVec<D>>,
+    labels: Vec<L>,
+}
+
+impl<const D: usize, L> Default for Dataset<D, L> {
+    fn default() -> Self {
+        Self {
+            inputs: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+}
+
+impl<const D: usize, L> Dataset<D, L> {
+    // CRUD-C: Constructors
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // CRUD-R: Properties
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+    pub fn inputs(&self) -> &[PerVec<D>] {
+        &self.inputs
+    }
+    pub fn labels(&self) -> &[L] {
+        &self.labels
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&PerVec<D>, &L)> {
+        self.inputs.iter().zip(self.labels.iter())
+    }
+    /// Returns sample weights balancing the classes. See [`class_balanced_weights`].
+    pub fn class_balanced_weights(&self) -> Vec<PerFloat>
+    where
+        L: Hash + Eq,
+    {
+        class_balanced_weights(&self.labels)
+    }
+
+    // CRUD-U: Modifiers
+
+    pub fn push(&mut self, input: PerVec<D>, label: L) {
+        self.inputs.push(input);
+        self.labels.push(label);
+    }
+
+    // CRUD-D: Consuming conversions
+
+    /// Converts every label with `f`, keeping the inputs.
+    pub fn map_labels<M>(self, f: impl FnMut(L) -> M) -> Dataset<D, M> {
+        Dataset {
+            inputs: self.inputs,
+            labels: self.labels.into_iter().map(f).collect(),
+        }
+    }
+    pub fn into_parts(self) -> (Vec<PerVec<D>>, Vec<L>) {
+        (self.inputs, self.labels)
+    }
+}
+
+impl<const D: usize, L> FromIterator<(PerVec<D>, L)> for Dataset<D, L> {
+    fn from_iter<T: IntoIterator<Item = (PerVec<D>, L)>>(iter: T) -> Self {
+        let (inputs, labels) = iter.into_iter().unzip();
+        Self { inputs, labels }
+    }
+}
+
+/// Returns a weight for every label, so that each class weighs the same in total.
+///
+/// Sample of class `c` gets `n / (k * n_c)`, where `n` is the number of samples, `k` the number of classes and `n_c` the size of class `c`.
+/// Weights average to `1`, so the overall learning rate stays the same.
+pub fn class_balanced_weights<L: Hash + Eq>(labels: &[L]) -> Vec<PerFloat> {
+    let mut class_sizes: HashMap<&L, usize> = HashMap::new();
+    for label in labels {
+        *class_sizes.entry(label).or_insert(0) += 1;
+    }
+    let samples_per_class = labels.len() as PerFloat / class_sizes.len() as PerFloat;
+    labels
+        .iter()
+        .map(|label| samples_per_class / class_sizes[label] as PerFloat)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balanced_weights_equalize_class_totals() {
+        let weights = class_balanced_weights(&['a', 'a', 'a', 'b']);
+        assert_eq!(weights, [2. / 3., 2. / 3., 2. / 3., 2.]);
+        assert_eq!(weights.iter().sum::<PerFloat>(), 4.);
+        assert!(class_balanced_weights::<char>(&[]).is_empty());
+    }
+
+    #[test]
+    fn dataset_keeps_inputs_paired_with_labels() {
+        let mut dataset: Dataset<1, char> = [(PerVec::<1>::new(1.), 'a')].into_iter().collect();
+        dataset.push(PerVec::<1>::new(2.), 'b');
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset.class_balanced_weights(), [1., 1.]);
+        let dataset = dataset.map_labels(|label| label == 'b');
+        assert_eq!(
+            dataset
+                .iter()
+                .map(|(input, &label)| (input[0], label))
+                .collect::<Vec<_>>(),
+            [(1., false), (2., true)]
+        );
+    }
+}
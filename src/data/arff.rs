@@ -0,0 +1,315 @@
+//! Parsing of the ARFF (Weka's Attribute-Relation File Format).
+//!
+//! Numeric attributes (`numeric`, `real`, `integer`) become input vector components in declaration order.
+//! The class attribute is the nominal attribute named `class`, or the last nominal attribute if none is named so.
+//! Its values become labels, which are indices into [`ArffData::classes`].
+//! Class value `?` is read as [`PartialLabel::Missing`], `!` marks a sample of none of the classes, read as [`PartialLabel::Reject`].
+//! Other attributes can't miss values.
+//! `string` and `date` attributes are skipped.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use anyhow::{anyhow, bail, Context};
+
+use super::{Dataset, PartialLabel};
+use crate::perceptron::PerVec;
+
+/// Data set read from an ARFF file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArffData<const D: usize> {
+    pub relation: String,
+    /// Nominal values of the class attribute. Known labels index into it.
+    pub classes: Vec<String>,
+    pub dataset: Dataset<D, PartialLabel<usize>>,
+}
+
+/// Type of an ARFF attribute.
+#[derive(Clone, Debug, PartialEq)]
+enum AttrType {
+    Numeric,
+    Nominal(Vec<String>),
+    Skipped,
+}
+
+/// Reads an ARFF data set with `D` numeric attributes.
+///
+/// Returned error reports what failed, not only why.
+pub fn read<R: BufRead, const D: usize>(reader: R) -> anyhow::Result<ArffData<D>> {
+    let mut relation = String::new();
+    let mut attributes: Vec<(String, AttrType)> = Vec::new();
+    let mut lines = reader.lines().enumerate();
+
+    // Header
+    loop {
+        let Some((line_idx, line)) = lines.next() else {
+            bail!("Missing `@data` section.");
+        };
+        let line_no = line_idx + 1;
+        let line = line.with_context(|| format!("Failed to read line {line_no}."))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        match keyword.to_ascii_lowercase().as_str() {
+            "@relation" => relation = unquote(rest.trim()).to_owned(),
+            "@attribute" => attributes.push(
+                parse_attribute(rest)
+                    .with_context(|| format!("Malformed attribute on line {line_no}."))?,
+            ),
+            "@data" => break,
+            _ => bail!("Unexpected header line {line_no}: \"{line}\"."),
+        }
+    }
+
+    // Attribute roles
+    let class_idx = attributes
+        .iter()
+        .position(|(name, ty)| {
+            name.eq_ignore_ascii_case("class") && matches!(ty, AttrType::Nominal(_))
+        })
+        .or_else(|| {
+            attributes
+                .iter()
+                .rposition(|(_, ty)| matches!(ty, AttrType::Nominal(_)))
+        })
+        .ok_or_else(|| anyhow!("No nominal attribute that could serve as the class."))?;
+    let AttrType::Nominal(classes) = attributes[class_idx].1.clone() else {
+        unreachable!("The class attribute was chosen among nominal ones.")
+    };
+    if let Some((name, _)) = attributes
+        .iter()
+        .enumerate()
+        .find(|&(idx, (_, ty))| idx != class_idx && matches!(ty, AttrType::Nominal(_)))
+        .map(|(_, attr)| attr)
+    {
+        bail!("Nominal attribute \"{name}\" can't be mapped to an input component.");
+    }
+    let numeric_count = attributes
+        .iter()
+        .filter(|(_, ty)| *ty == AttrType::Numeric)
+        .count();
+    if numeric_count != D {
+        bail!("Expected {D} numeric attributes, found {numeric_count}.");
+    }
+
+    // Data
+    let mut dataset = Dataset::new();
+    for (line_idx, line) in lines {
+        let line_no = line_idx + 1;
+        let line = line.with_context(|| format!("Failed to read line {line_no}."))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+        let (input, label) = parse_row(line, &attributes, class_idx, &classes)
+            .with_context(|| format!("Malformed data row on line {line_no}."))?;
+        dataset.push(input, label);
+    }
+    Ok(ArffData {
+        relation,
+        classes,
+        dataset,
+    })
+}
+
+/// Reads an ARFF data set from file.
+///
+/// Returned error reports what failed, not only why.
+pub fn read_path<const D: usize>(path: impl AsRef<Path>) -> anyhow::Result<ArffData<D>> {
+    let path = path.as_ref();
+    (|| read(BufReader::new(File::open(path)?)))()
+        .with_context(|| format!("Failed to read ARFF data from \"{}\".", path.display()))
+}
+
+/// Parses what follows `@attribute`.
+fn parse_attribute(decl: &str) -> anyhow::Result<(String, AttrType)> {
+    let decl = decl.trim();
+    let (name, ty) = match decl.chars().next() {
+        Some(quote @ ('\'' | '"')) => {
+            let end = decl[1..]
+                .find(quote)
+                .ok_or_else(|| anyhow!("Unterminated attribute name."))?;
+            (&decl[1..=end], &decl[end + 2..])
+        }
+        _ => decl
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| anyhow!("Missing attribute type."))?,
+    };
+    let ty = ty.trim();
+    let ty = if let Some(values) = ty.strip_prefix('{') {
+        let values = values
+            .strip_suffix('}')
+            .ok_or_else(|| anyhow!("Unterminated nominal specification."))?;
+        AttrType::Nominal(split_values(values).map(str::to_owned).collect())
+    } else {
+        let keyword = ty.split_whitespace().next().unwrap_or_default();
+        match keyword.to_ascii_lowercase().as_str() {
+            "numeric" | "real" | "integer" => AttrType::Numeric,
+            "string" | "date" => AttrType::Skipped,
+            _ => bail!("Unsupported attribute type \"{ty}\"."),
+        }
+    };
+    Ok((name.to_owned(), ty))
+}
+
+/// Parses a dense data row.
+fn parse_row<const D: usize>(
+    row: &str,
+    attributes: &[(String, AttrType)],
+    class_idx: usize,
+    classes: &[String],
+) -> anyhow::Result<(PerVec<D>, PartialLabel<usize>)> {
+    if row.starts_with('{') {
+        bail!("Sparse ARFF rows aren't supported.");
+    }
+    let values: Vec<&str> = split_values(row).collect();
+    if values.len() != attributes.len() {
+        bail!(
+            "Expected {} values, found {}.",
+            attributes.len(),
+            values.len()
+        );
+    }
+    let mut input = PerVec::<D>::zeros();
+    let mut components = input.iter_mut();
+    let mut label = None;
+    for (idx, (value, (name, ty))) in values.into_iter().zip(attributes).enumerate() {
+        match ty {
+            AttrType::Nominal(_) if idx == class_idx && value == "?" => {
+                label = Some(PartialLabel::Missing);
+            }
+            _ if value == "?" => bail!("Attribute \"{name}\" has a missing value."),
+            AttrType::Numeric => {
+                *components
+                    .next()
+                    .expect("Numeric attributes were counted to be `D`.") = value
+                    .parse()
+                    .with_context(|| format!("Value \"{value}\" of \"{name}\" isn't a number."))?;
+            }
+            AttrType::Nominal(_) if idx == class_idx && value == "!" => {
+                label = Some(PartialLabel::Reject);
+            }
+            AttrType::Nominal(_) if idx == class_idx => {
+                label = Some(PartialLabel::Known(
+                    classes
+                        .iter()
+                        .position(|class| class == value)
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "Value \"{value}\" isn't declared for class attribute \"{name}\"."
+                            )
+                        })?,
+                ));
+            }
+            AttrType::Nominal(_) | AttrType::Skipped => {}
+        }
+    }
+    Ok((
+        input,
+        label.expect("The class attribute is one of the attributes."),
+    ))
+}
+
+/// Splits comma separated values, removing surrounding whitespace and quotes.
+fn split_values(values: &str) -> impl Iterator<Item = &str> {
+    let mut rest = Some(values);
+    std::iter::from_fn(move || {
+        let remaining = rest?.trim_start();
+        let end = match remaining.chars().next() {
+            Some(quote @ ('\'' | '"')) => remaining[1..]
+                .find(quote)
+                .map(|closing| closing + 2)
+                .and_then(|after_quote| {
+                    remaining[after_quote..]
+                        .find(',')
+                        .map(|comma| comma + after_quote)
+                }),
+            _ => remaining.find(','),
+        };
+        let (value, new_rest) = match end {
+            Some(comma) => (&remaining[..comma], Some(&remaining[comma + 1..])),
+            None => (remaining, None),
+        };
+        rest = new_rest;
+        Some(unquote(value.trim()))
+    })
+}
+
+/// Removes one level of matching single or double quotes.
+fn unquote(s: &str) -> &str {
+    for quote in ['\'', '"'] {
+        if let Some(inner) = s
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IRISES: &str = "\
+% Comment
+@RELATION iris
+@ATTRIBUTE sepallength NUMERIC
+@ATTRIBUTE 'sepal width' REAL
+@ATTRIBUTE petallength NUMERIC
+@ATTRIBUTE petalwidth NUMERIC
+@ATTRIBUTE class {Iris-setosa,Iris-versicolor,'Iris-virginica'}
+@DATA
+5.1,3.5,1.4,0.2,Iris-setosa
+7.0, 3.2, 4.7, 1.4, Iris-versicolor
+
+6.3,3.3,6.0,2.5,'Iris-virginica'
+5.0,2.0,3.5,1.0,!
+4.9,3.0,1.4,0.2,?
+";
+
+    #[test]
+    fn reads_numeric_attributes_and_class() -> anyhow::Result<()> {
+        let data = read::<_, 4>(IRISES.as_bytes())?;
+        assert_eq!(data.relation, "iris");
+        assert_eq!(
+            data.classes,
+            ["Iris-setosa", "Iris-versicolor", "Iris-virginica"]
+        );
+        assert_eq!(data.dataset.len(), 5);
+        assert_eq!(
+            data.dataset.inputs()[1],
+            PerVec::<4>::new(7.0, 3.2, 4.7, 1.4)
+        );
+        assert_eq!(
+            data.dataset.labels(),
+            [
+                PartialLabel::Known(0),
+                PartialLabel::Known(1),
+                PartialLabel::Known(2),
+                PartialLabel::Reject,
+                PartialLabel::Missing
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_undeclared_class_and_missing_values() {
+        for row in ["5.1,3.5,1.4,0.2,Iris-unknown", "5.1,?,1.4,0.2,Iris-setosa"] {
+            let text = IRISES.replace("5.0,2.0,3.5,1.0,!", row);
+            assert!(read::<_, 4>(text.as_bytes()).is_err(), "{row}");
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_number_of_numeric_attributes() {
+        assert!(read::<_, 3>(IRISES.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Reading and writing of the LIBSVM (a.k.a. SVMlight) sparse text format.
+//!
+//! Every line holds one sample: `label index:value index:value ...`.
+//! Indices are 1-based and strictly ascending. Features that aren't listed are implicitly zero.
+//! Everything after `#` is a comment.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+
+use super::Dataset;
+use crate::{perceptron::PerVec, util::SparseVec};
+
+/// Class label of a LIBSVM sample.
+///
+/// Binary data sets use `-1`/`+1`, multi-class ones any integers.
+pub type SvmLabel = i64;
+
+/// Reads a LIBSVM data set with `D` features.
+///
+/// Returned error reports what failed, not only why.
+pub fn read<R: BufRead, const D: usize>(reader: R) -> anyhow::Result<Dataset<D, SvmLabel>> {
+    let mut dataset = Dataset::new();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let line = line.with_context(|| format!("Failed to read line {line_no}."))?;
+        let Some((input, label)) =
+            parse_dense_line(&line).with_context(|| format!("Malformed LIBSVM line {line_no}."))?
+        else {
+            continue;
+        };
+        dataset.push(input, label);
+    }
+    Ok(dataset)
+}
+
+/// Reads a LIBSVM data set from file.
+///
+/// Returned error reports what failed, not only why.
+pub fn read_path<const D: usize>(path: impl AsRef<Path>) -> anyhow::Result<Dataset<D, SvmLabel>> {
+    let path = path.as_ref();
+    (|| read(BufReader::new(File::open(path)?)))()
+        .with_context(|| format!("Failed to read LIBSVM data from \"{}\".", path.display()))
+}
+
+/// Reads a LIBSVM data set into sparse inputs of any dimension.
+///
+/// Feature `index` lands at component `index - 1`.
+///
+/// Returned error reports what failed, not only why.
+pub fn read_sparse<R: BufRead>(reader: R) -> anyhow::Result<(Vec<SparseVec>, Vec<SvmLabel>)> {
+    let mut inputs = Vec::new();
+    let mut labels = Vec::new();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line_no = line_idx + 1;
+        let line = line.with_context(|| format!("Failed to read line {line_no}."))?;
+        let Some((input, label)) =
+            parse_line(&line).with_context(|| format!("Malformed LIBSVM line {line_no}."))?
+        else {
+            continue;
+        };
+        inputs.push(input);
+        labels.push(label);
+    }
+    Ok((inputs, labels))
+}
+
+/// Parses one line into a dense input of dimension `D`.
+fn parse_dense_line<const D: usize>(line: &str) -> anyhow::Result<Option<(PerVec<D>, SvmLabel)>> {
+    let Some((sparse, label)) = parse_line(line)? else {
+        return Ok(None);
+    };
+    if sparse.min_dim() > D {
+        bail!(
+            "Feature index {} exceeds the expected dimension {D}.",
+            sparse.min_dim()
+        );
+    }
+    let mut input = PerVec::<D>::zeros();
+    for (idx, value) in sparse.iter() {
+        input[idx] = value;
+    }
+    Ok(Some((input, label)))
+}
+
+/// Parses one line. Returns `None` for lines without a sample (blank or comment only).
+fn parse_line(line: &str) -> anyhow::Result<Option<(SparseVec, SvmLabel)>> {
+    let line = line.split_once('#').map_or(line, |(data, _comment)| data);
+    let mut tokens = line.split_whitespace();
+    let Some(label) = tokens.next() else {
+        return Ok(None);
+    };
+    let label = label
+        .trim_start_matches('+')
+        .parse()
+        .with_context(|| format!("Label \"{label}\" isn't an integer."))?;
+
+    let mut input = SparseVec::new();
+    let mut prev_index = 0;
+    for feature in tokens {
+        let Some((index, value)) = feature.split_once(':') else {
+            bail!("Feature \"{feature}\" isn't in `index:value` form.");
+        };
+        let index: usize = index
+            .parse()
+            .with_context(|| format!("Feature index \"{index}\" isn't a positive integer."))?;
+        if index <= prev_index {
+            bail!("Feature indices must be positive and strictly ascending, got {index} after {prev_index}.");
+        }
+        let value = value
+            .parse()
+            .with_context(|| format!("Feature value \"{value}\" isn't a number."))?;
+        input.push(index - 1, value);
+        prev_index = index;
+    }
+    Ok(Some((input, label)))
+}
+
+/// Writes `dataset` in LIBSVM format. Zero features are omitted.
+pub fn write<W, const D: usize, L>(writer: W, dataset: &Dataset<D, L>) -> anyhow::Result<()>
+where
+    W: Write,
+    L: std::fmt::Display,
+{
+    let mut writer = BufWriter::new(writer);
+    for (input, label) in dataset.iter() {
+        write!(writer, "{label}")?;
+        for (idx, value) in input.iter().enumerate().filter(|(_, v)| **v != 0.) {
+            write!(writer, " {}:{value}", idx + 1)?;
+        }
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes `dataset` in LIBSVM format into a newly created file.
+///
+/// Returned error reports what failed, not only why.
+pub fn write_path<const D: usize, L>(
+    path: impl AsRef<Path>,
+    dataset: &Dataset<D, L>,
+) -> anyhow::Result<()>
+where
+    L: std::fmt::Display,
+{
+    let path = path.as_ref();
+    (|| write(File::create(path)?, dataset))()
+        .with_context(|| format!("Failed to write LIBSVM data to \"{}\".", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: &str = "\
+# Comment
++1 1:0.5 3:-2
+-1 2:1 # Trailing comment
+
+3
+";
+
+    #[test]
+    fn reads_dense_samples() -> anyhow::Result<()> {
+        let dataset = read::<_, 3>(SAMPLES.as_bytes())?;
+        assert_eq!(dataset.labels(), [1, -1, 3]);
+        assert_eq!(
+            dataset.inputs(),
+            [
+                PerVec::<3>::new(0.5, 0., -2.),
+                PerVec::<3>::new(0., 1., 0.),
+                PerVec::<3>::zeros(),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reads_sparse_samples_of_any_dimension() -> anyhow::Result<()> {
+        let (inputs, labels) = read_sparse("1 1000:2.5\n-1 1:1 7:3\n".as_bytes())?;
+        assert_eq!(labels, [1, -1]);
+        assert_eq!(inputs[0].iter().collect::<Vec<_>>(), [(999, 2.5)]);
+        assert_eq!(inputs[1].iter().collect::<Vec<_>>(), [(0, 1.), (6, 3.)]);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for line in [
+            "x 1:1",
+            "1 1",
+            "1 0:1",
+            "1 2:1 1:1",
+            "1 1:1 1:2",
+            "1 1:x",
+            "1 4:1",
+        ] {
+            assert!(read::<_, 3>(line.as_bytes()).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn written_dataset_reads_back() -> anyhow::Result<()> {
+        let dataset = read::<_, 3>(SAMPLES.as_bytes())?;
+        let mut text = Vec::new();
+        write(&mut text, &dataset)?;
+        assert_eq!(
+            String::from_utf8(text.clone())?,
+            "1 1:0.5 3:-2\n-1 2:1\n3\n"
+        );
+        assert_eq!(read::<_, 3>(&text[..])?, dataset);
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+//! Reading and writing of multi-label data as CSV.
+//!
+//! Every record holds one sample: `D` feature columns followed by `N` label columns.
+//! A label column is `1`/`0` or `true`/`false` (case-insensitive).
+
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+
+use super::Dataset;
+use crate::{multi_label::LabelSet, perceptron::PerVec};
+
+/// Reads a multi-label data set with `D` features and `N` labels. The first record is skipped if `has_headers`.
+///
+/// Returned error reports what failed, not only why.
+pub fn read<R: Read, const D: usize, const N: usize>(
+    reader: R,
+    has_headers: bool,
+) -> anyhow::Result<Dataset<D, LabelSet<N>>> {
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(has_headers)
+        .from_reader(reader);
+    let mut dataset = Dataset::new();
+    for (record_idx, record) in csv_reader.records().enumerate() {
+        let record_no = record_idx + 1;
+        let record = record.with_context(|| format!("Failed to read record {record_no}."))?;
+        let (input, labels) =
+            parse_record(&record).with_context(|| format!("Malformed record {record_no}."))?;
+        dataset.push(input, labels);
+    }
+    Ok(dataset)
+}
+
+/// Reads a multi-label data set from file.
+///
+/// Returned error reports what failed, not only why.
+pub fn read_path<const D: usize, const N: usize>(
+    path: impl AsRef<Path>,
+    has_headers: bool,
+) -> anyhow::Result<Dataset<D, LabelSet<N>>> {
+    let path = path.as_ref();
+    (|| read(File::open(path)?, has_headers))().with_context(|| {
+        format!(
+            "Failed to read multi-label data from \"{}\".",
+            path.display()
+        )
+    })
+}
+
+/// Writes `dataset` without headers, with labels as `1`/`0`.
+///
+/// Returned error reports what failed, not only why.
+pub fn write<W: Write, const D: usize, const N: usize>(
+    writer: W,
+    dataset: &Dataset<D, LabelSet<N>>,
+) -> anyhow::Result<()> {
+    let mut csv_writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    for (sample_idx, (input, labels)) in dataset.iter().enumerate() {
+        let features = input.iter().map(|feature| feature.to_string());
+        let labels = labels.0.iter().map(|&label| u8::from(label).to_string());
+        csv_writer
+            .write_record(features.chain(labels))
+            .with_context(|| format!("Failed to write sample {}.", sample_idx + 1))?;
+    }
+    csv_writer.flush()?;
+    Ok(())
+}
+
+/// Writes `dataset` into a newly created file.
+///
+/// Returned error reports what failed, not only why.
+pub fn write_path<const D: usize, const N: usize>(
+    path: impl AsRef<Path>,
+    dataset: &Dataset<D, LabelSet<N>>,
+) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    (|| write(File::create(path)?, dataset))().with_context(|| {
+        format!(
+            "Failed to write multi-label data to \"{}\".",
+            path.display()
+        )
+    })
+}
+
+fn parse_record<const D: usize, const N: usize>(
+    record: &csv::StringRecord,
+) -> anyhow::Result<(PerVec<D>, LabelSet<N>)> {
+    if record.len() != D + N {
+        bail!(
+            "Expected {D} features and {N} labels, but found {} columns.",
+            record.len()
+        );
+    }
+    let mut input = PerVec::<D>::zeros();
+    for (component, field) in input.iter_mut().zip(record.iter()) {
+        *component = field
+            .trim()
+            .parse()
+            .with_context(|| format!("\"{field}\" isn't a number."))?;
+    }
+    let mut labels = [false; N];
+    for (label, field) in labels.iter_mut().zip(record.iter().skip(D)) {
+        *label = match field.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" => true,
+            "0" | "false" => false,
+            _ => bail!("\"{field}\" isn't a label value."),
+        };
+    }
+    Ok((input, LabelSet(labels)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_labels_in_any_case_and_writes_them_as_digits() -> anyhow::Result<()> {
+        let text = "x,y,a,b\n1.5,-2,1,FALSE\n0,3, true ,0\n";
+        let dataset = read::<_, 2, 2>(text.as_bytes(), true)?;
+        assert_eq!(dataset.labels(), [LabelSet([true, false]); 2]);
+        assert_eq!(dataset.inputs()[0], PerVec::<2>::new(1.5, -2.));
+
+        let mut written = Vec::new();
+        write(&mut written, &dataset)?;
+        assert_eq!(String::from_utf8(written.clone())?, "1.5,-2,1,0\n0,3,1,0\n");
+        assert_eq!(read::<_, 2, 2>(&written[..], false)?, dataset);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_malformed_records() {
+        for text in ["1,1\n", "1,1,1,1\n", "x,1,1\n", "1,1,yes\n"] {
+            assert!(read::<_, 2, 1>(text.as_bytes(), false).is_err(), "{text}");
+        }
+    }
+}
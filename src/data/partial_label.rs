@@ -0,0 +1,154 @@
+//! Labels known only partially, or not at all.
+
+use crate::util::{BoolExpect, BoolExpectation, IntExpect};
+
+/// Label of a training sample, which can be less certain than a single class.
+///
+/// As an [`IntExpect`], it demands only the output bits its possible classes agree on.
+/// Bits they disagree on become [`BoolExpectation::NoExpect`], so the sample still trains the other bits.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PartialLabel<L> {
+    Known(L),
+    /// Sample without label. It demands nothing, so it doesn't change weights and counts as decided correctly.
+    Missing,
+    /// Sample of one of these classes.
+    OneOf(Vec<L>),
+    /// Sample of none of the modeled classes. It demands the output reserved by [`IntExpect::reject_output`].
+    Reject,
+}
+
+impl<L> PartialLabel<L> {
+    // CRUD-R: Properties
+
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Self::Missing)
+    }
+    pub fn is_reject(&self) -> bool {
+        matches!(self, Self::Reject)
+    }
+    /// Classes the sample can be of. Empty for missing and reject labels.
+    pub fn candidates(&self) -> &[L] {
+        match self {
+            Self::Known(label) => std::slice::from_ref(label),
+            Self::Missing | Self::Reject => &[],
+            Self::OneOf(labels) => labels,
+        }
+    }
+
+    // CRUD-D: Consuming conversions
+
+    pub fn map<M>(self, mut f: impl FnMut(L) -> M) -> PartialLabel<M> {
+        match self {
+            Self::Known(label) => PartialLabel::Known(f(label)),
+            Self::Missing => PartialLabel::Missing,
+            Self::OneOf(labels) => PartialLabel::OneOf(labels.into_iter().map(f).collect()),
+            Self::Reject => PartialLabel::Reject,
+        }
+    }
+}
+
+impl<L> From<L> for PartialLabel<L> {
+    fn from(label: L) -> Self {
+        Self::Known(label)
+    }
+}
+
+impl<L: IntExpect> IntExpect for PartialLabel<L> {
+    type ProvidedInt = L::ProvidedInt;
+    type BoolExpectation = BoolExpectation;
+
+    fn is_met_by(&self, value: Self::ProvidedInt) -> bool {
+        match self {
+            Self::Missing => true,
+            Self::Reject => value == Self::reject_output(),
+            _ => self.candidates().iter().any(|label| label.is_met_by(value)),
+        }
+    }
+
+    fn bit_expectation(&self, idx: usize) -> Self::BoolExpectation {
+        if self.is_reject() {
+            return Self::reject_output().bit_expectation(idx).expectation();
+        }
+        let mut agreed = None;
+        for label in self.candidates() {
+            match (label.bit_expectation(idx).expectation(), agreed) {
+                (BoolExpectation::NoExpect, _) => return BoolExpectation::NoExpect,
+                (BoolExpectation::Expect(bit), None) => agreed = Some(bit),
+                (BoolExpectation::Expect(bit), Some(agreed_bit)) if bit != agreed_bit => {
+                    return BoolExpectation::NoExpect
+                }
+                (BoolExpectation::Expect(_), Some(_)) => {}
+            }
+        }
+        agreed.map_or(BoolExpectation::NoExpect, BoolExpectation::Expect)
+    }
+
+    fn reject_output() -> Self::ProvidedInt {
+        L::reject_output()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_label_demands_its_bits() {
+        let label = PartialLabel::Known(0b10u8);
+        assert!(label.is_met_by(0b10));
+        assert!(!label.is_met_by(0b11));
+        assert_eq!(label.bit_expectation(0), BoolExpectation::Expect(false));
+        assert_eq!(label.bit_expectation(1), BoolExpectation::Expect(true));
+    }
+
+    #[test]
+    fn one_of_label_demands_only_agreed_bits() {
+        let label = PartialLabel::OneOf(vec![0b10u8, 0b11]);
+        assert!(label.is_met_by(0b10) && label.is_met_by(0b11));
+        assert!(!label.is_met_by(0b01));
+        assert_eq!(label.bit_expectation(0), BoolExpectation::NoExpect);
+        assert_eq!(label.bit_expectation(1), BoolExpectation::Expect(true));
+    }
+
+    #[test]
+    fn missing_label_is_met_by_anything() {
+        let label = PartialLabel::<u8>::Missing;
+        assert!(label.is_met_by(0b00) && label.is_met_by(0b11));
+        assert_eq!(label.bit_expectation(0), BoolExpectation::NoExpect);
+    }
+
+    #[test]
+    fn reject_label_demands_the_reject_output() {
+        let label = PartialLabel::<u8>::Reject;
+        assert!(label.is_reject());
+        assert!(label.is_met_by(0b00));
+        assert!((1..4).all(|output| !label.is_met_by(output)));
+        assert_eq!(label.bit_expectation(0), BoolExpectation::Expect(false));
+        assert_eq!(label.bit_expectation(1), BoolExpectation::Expect(false));
+    }
+
+    #[test]
+    fn network_learns_reject_label() {
+        use crate::{perceptron::PerVec, OneLayerNN, StoppingCriteria};
+
+        let inputs = [-2., -1., 1., 2.].map(PerVec::<1>::new);
+        let labels = [
+            PartialLabel::Reject,
+            PartialLabel::Reject,
+            PartialLabel::Known(0b11u8),
+            PartialLabel::Known(0b11),
+        ];
+        let mut network = OneLayerNN::<2, 1>::default();
+        network.fit::<_, _, u8, _>(
+            &inputs,
+            labels.clone(),
+            StoppingCriteria::with_max_epochs(20),
+            None,
+        );
+        assert_eq!(
+            network.accuracy_for(&inputs, labels),
+            Some(num_rational::Ratio::from_integer(1))
+        );
+        assert_eq!(network.decide_for::<u8>(&inputs[0]), 0b00);
+    }
+}
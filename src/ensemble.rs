@@ -0,0 +1,7 @@
+//! Meta-classifiers combining many networks into one.
+
+pub use adaboost::AdaBoost;
+pub mod adaboost;
+
+pub use bagging::BaggingEnsemble;
+pub mod bagging;
@@ -0,0 +1,269 @@
+//! Adaptive boosting of [`OneLayerNN`]s with the SAMME rule for multi-class data.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use num_rational::Ratio;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    nn::one_layer::OutputInt,
+    perceptron::{PerFloat, PerVec},
+    util::IntExpect,
+    Neuron, OneLayerNN, Perceptron,
+};
+
+/// Network trained in one boosting round.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(bound(
+    serialize = "U: serde::Serialize",
+    deserialize = "U: serde::Deserialize<'de>"
+))]
+pub struct WeakLearner<const N: usize, const D: usize, U = Perceptron<D>> {
+    pub network: OneLayerNN<N, D, U>,
+    /// Weighted training error in its round.
+    pub error: f64,
+    /// Weight of its vote.
+    pub vote_weight: f64,
+}
+
+/// Sequence of networks, each trained with more weight on samples its predecessors got wrong.
+///
+/// A single-unit network ([`OneLayerNN<1, D>`]) makes a plain [`Perceptron`] weak learner.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(bound(
+    serialize = "U: serde::Serialize",
+    deserialize = "U: serde::Deserialize<'de>"
+))]
+pub struct AdaBoost<const N: usize, const D: usize, U = Perceptron<D>> {
+    learners: Vec<WeakLearner<N, D, U>>,
+}
+
+impl<const N: usize, const D: usize, U> AdaBoost<N, D, U>
+where
+    U: Neuron<PerVec<D>> + Send + Sync,
+    OneLayerNN<N, D, U>: Default,
+{
+    // CRUD-C: Constructors
+
+    /// Runs up to `rounds` boosting rounds (SAMME), where outputs mean classes as mapped by `decode`.
+    ///
+    /// The number of classes SAMME needs is the number of classes `decode` tells apart, see [`Self::class_count`].
+    /// Pass the same `decode` to [`Self::decide_with`] later.
+    /// In each round a fresh network is trained for `epochs_per_round` epochs with [`OneLayerNN::train_on_weighted`].
+    /// Samples are visited in an order shuffled by RNG seeded with `seed`, as perceptrons trained on data sorted by class end up biased towards the last class.
+    /// Boosting stops early when a learner is perfect or no better than random guessing.
+    pub fn fit<I, E, C>(
+        inputs: &[PerVec<D>],
+        expecteds: &[E],
+        rounds: usize,
+        decode: impl Fn(I) -> C,
+        epochs_per_round: usize,
+        seed: u64,
+    ) -> Self
+    where
+        I: OutputInt,
+        E: IntExpect<ProvidedInt = I> + Clone + Sync + Send,
+        C: Ord,
+    {
+        let sample_count = inputs.len().min(expecteds.len());
+        let class_count = Self::class_count(decode) as f64;
+        let mut sample_weights = vec![1. / sample_count as f64; sample_count];
+        let mut learners = Vec::with_capacity(rounds);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut order: Vec<usize> = (0..sample_count).collect();
+
+        for _ in 0..rounds {
+            let mut network = OneLayerNN::<N, D, U>::default();
+            for _ in 0..epochs_per_round {
+                order.shuffle(&mut rng);
+                // Scaled to average 1, so that the learning rate stays the usual one.
+                network.train_on_weighted(
+                    order.iter().map(|&idx| &inputs[idx]),
+                    order.iter().map(|&idx| expecteds[idx].clone()),
+                    order
+                        .iter()
+                        .map(|&idx| (sample_weights[idx] * sample_count as f64) as PerFloat),
+                );
+            }
+            let misses: Vec<bool> = inputs
+                .iter()
+                .zip(expecteds)
+                .map(|(input, expected)| !expected.is_met_by(network.decide_for::<I>(input)))
+                .collect();
+            let error: f64 = misses
+                .iter()
+                .zip(&sample_weights)
+                .filter(|(&miss, _)| miss)
+                .map(|(_, w)| w)
+                .sum();
+            if error >= 1. - 1. / class_count {
+                // No better than random ==> more rounds won't help.
+                break;
+            }
+            let clamped_error = error.max(f64::EPSILON);
+            let vote_weight = ((1. - clamped_error) / clamped_error).ln() + (class_count - 1.).ln();
+            learners.push(WeakLearner {
+                network,
+                error,
+                vote_weight,
+            });
+            if error == 0. {
+                // Perfect learner ==> nothing left to focus on.
+                break;
+            }
+            // Reweighting
+            for (w, _) in sample_weights
+                .iter_mut()
+                .zip(&misses)
+                .filter(|(_, &miss)| miss)
+            {
+                *w *= vote_weight.exp();
+            }
+            let weight_sum: f64 = sample_weights.iter().sum();
+            sample_weights.iter_mut().for_each(|w| *w /= weight_sum);
+        }
+        Self { learners }
+    }
+
+    // CRUD-R: Properties
+
+    pub fn learners(&self) -> &[WeakLearner<N, D, U>] {
+        &self.learners
+    }
+    /// Number of classes `decode` maps outputs of `N` units to, but at least 2.
+    ///
+    /// E.g. `N` plain bits make `2^N` classes, while iris codes `0b10` and `0b11` both mean Setosa and make 3.
+    pub fn class_count<I, C>(decode: impl Fn(I) -> C) -> usize
+    where
+        I: OutputInt,
+        C: Ord,
+    {
+        let classes: BTreeSet<C> = (0..1_u64 << N)
+            .filter_map(num_traits::cast::<u64, I>)
+            .map(decode)
+            .collect();
+        classes.len().max(2)
+    }
+    /// Returns the output with the greatest total vote weight. Ties go to the lowest output.
+    ///
+    /// # Panics
+    /// * If boosting produced no learner.
+    pub fn decide_for<I: OutputInt>(&self, input: &PerVec<D>) -> I {
+        self.decide_with(input, |output: I| output)
+    }
+    /// Returns the class with the greatest total vote weight, where outputs are mapped to classes with `decode`.
+    ///
+    /// Use it when several outputs mean the same class, so that their votes add up.
+    /// Ties go to the lowest class.
+    ///
+    /// # Panics
+    /// * If boosting produced no learner.
+    pub fn decide_with<I, C, F>(&self, input: &PerVec<D>, decode: F) -> C
+    where
+        I: OutputInt,
+        C: Ord,
+        F: Fn(I) -> C,
+    {
+        let mut votes: BTreeMap<C, f64> = BTreeMap::new();
+        for learner in &self.learners {
+            let class = decode(learner.network.decide_for(input));
+            *votes.entry(class).or_insert(0.) += learner.vote_weight;
+        }
+        votes
+            .into_iter()
+            // First of the greatest, i.e. the lowest class among tied ones.
+            .min_by(|(_, lhs), (_, rhs)| rhs.total_cmp(lhs))
+            .map(|(class, _)| class)
+            .expect("Boosting should have produced at least one learner.")
+    }
+    /// Returns share of samples for which [`Self::decide_for`] meets the expectation.
+    pub fn accuracy_for<'i, II, EI, I, E>(&self, inputs: II, expected: EI) -> Option<Ratio<u64>>
+    where
+        II: IntoIterator<Item = &'i PerVec<D>>,
+        EI: IntoIterator<Item = E>,
+        I: OutputInt,
+        E: IntExpect<ProvidedInt = I>,
+    {
+        let [mut correct, mut all] = [0, 0];
+        for (input, expectation) in inputs.into_iter().zip(expected) {
+            if expectation.is_met_by(self.decide_for::<I>(input)) {
+                correct += 1;
+            }
+            all += 1;
+        }
+        (all != 0).then(|| Ratio::new(correct, all))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Learner whose units all decide `decision` for non-negative inputs.
+    fn constant_learner(decision: bool, vote_weight: f64) -> WeakLearner<2, 1> {
+        let mut network = OneLayerNN::<2, 1>::default();
+        for perceptron in network.neurons_mut() {
+            perceptron.set_theta(if decision { 0. } else { 1. });
+        }
+        WeakLearner {
+            network,
+            error: 0.,
+            vote_weight,
+        }
+    }
+
+    #[test]
+    fn class_count_follows_decoder() {
+        assert_eq!(AdaBoost::<2, 1>::class_count(|output: u8| output), 4);
+        assert_eq!(
+            AdaBoost::<2, 1>::class_count(|output: u8| output.min(0b10)),
+            3
+        );
+        assert_eq!(AdaBoost::<1, 1>::class_count(|_: u8| ()), 2);
+    }
+
+    #[test]
+    fn fitting_learns_separable_data() {
+        let (inputs, expecteds): (Vec<_>, Vec<_>) = (-5..=5)
+            .filter(|&x| x != 0)
+            .map(|x| (PerVec::<1>::new(x as f32), u8::from(x > 0)))
+            .unzip();
+        let boost =
+            AdaBoost::<1, 1>::fit::<u8, _, _>(&inputs, &expecteds, 5, |output: u8| output, 5, 3);
+        assert!(!boost.learners().is_empty());
+        assert_eq!(
+            boost.accuracy_for(&inputs, expecteds.iter().copied()),
+            Some(Ratio::from_integer(1))
+        );
+    }
+
+    #[test]
+    fn tied_votes_go_to_lowest_output() {
+        let input = PerVec::<1>::zeros();
+        for learners in [
+            vec![constant_learner(true, 1.), constant_learner(false, 1.)],
+            vec![constant_learner(false, 1.), constant_learner(true, 1.)],
+        ] {
+            let boost = AdaBoost { learners };
+            assert_eq!(boost.decide_for::<u8>(&input), 0b00);
+        }
+    }
+
+    #[test]
+    fn votes_for_same_output_add_up() {
+        let input = PerVec::<1>::zeros();
+        let mut learners = vec![constant_learner(false, 0.4), constant_learner(true, 0.5)];
+        assert_eq!(
+            AdaBoost {
+                learners: learners.clone()
+            }
+            .decide_for::<u8>(&input),
+            0b11
+        );
+
+        learners.push(constant_learner(false, 0.3));
+        let boost = AdaBoost { learners };
+        assert_eq!(boost.decide_for::<u8>(&input), 0b00);
+        assert!(!boost.decide_with(&input, |output: u8| output == 0b11));
+    }
+}
@@ -0,0 +1,287 @@
+//! Bootstrap aggregating (bagging) of [`OneLayerNN`]s.
+
+use std::collections::BTreeMap;
+
+use num_rational::Ratio;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    nn::one_layer::OutputInt, perceptron::PerVec, util::IntExpect, LinearNeuron, Neuron,
+    OneLayerNN, Perceptron,
+};
+
+/// Networks trained on bootstrap resamples of the same data, deciding together by vote.
+///
+/// Each member votes for its whole output, so a class is never assembled from bits no member decided together.
+/// Members using different codes for the same class support each other through [`Self::decide_with`].
+/// Persist it with [`crate::persist`].
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(bound(
+    serialize = "U: serde::Serialize",
+    deserialize = "U: serde::Deserialize<'de>"
+))]
+pub struct BaggingEnsemble<const N: usize, const D: usize, U = Perceptron<D>> {
+    members: Vec<OneLayerNN<N, D, U>>,
+    /// Accuracy on samples left out of members' resamples, measured when fitting.
+    oob_accuracy: Option<Ratio<u64>>,
+}
+
+impl<const N: usize, const D: usize, U> BaggingEnsemble<N, D, U>
+where
+    U: Neuron<PerVec<D>> + Send + Sync,
+    OneLayerNN<N, D, U>: Default,
+{
+    // CRUD-C: Constructors
+
+    /// Trains `member_count` networks in parallel, each on its own bootstrap resample.
+    ///
+    /// Members are trained with [`OneLayerNN::fit_to`].
+    /// Resamples are drawn from an RNG seeded by `seed`, so the result is reproducible.
+    pub fn fit<I, E>(
+        inputs: &[PerVec<D>],
+        expecteds: &[E],
+        member_count: usize,
+        max_progress_reattemps: u64,
+        seed: u64,
+    ) -> Self
+    where
+        I: OutputInt,
+        E: IntExpect<ProvidedInt = I> + Clone + Sync + Send,
+    {
+        let sample_count = inputs.len().min(expecteds.len());
+        let trained: Vec<(OneLayerNN<N, D, U>, Vec<bool>)> = (0..member_count)
+            .into_par_iter()
+            .map(|member_idx| {
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(member_idx as u64));
+                let resample: Vec<usize> = (0..sample_count)
+                    .map(|_| rng.gen_range(0..sample_count))
+                    .collect();
+                let mut in_bag = vec![false; sample_count];
+                for &idx in &resample {
+                    in_bag[idx] = true;
+                }
+                let mut member = OneLayerNN::default();
+                member.fit_to(
+                    resample.iter().map(|&idx| &inputs[idx]),
+                    resample.iter().map(|&idx| expecteds[idx].clone()),
+                    max_progress_reattemps,
+                );
+                (member, in_bag)
+            })
+            .collect();
+
+        // Each sample is judged only by members that haven't seen it.
+        let [mut correct, mut all] = [0, 0];
+        for (sample_idx, (input, expected)) in inputs.iter().zip(expecteds).enumerate() {
+            let judges = trained
+                .iter()
+                .filter(|(_, in_bag)| !in_bag[sample_idx])
+                .map(|(member, _)| member);
+            if let Some(decision) = Self::plurality_vote(judges, input, |output: I| output) {
+                if expected.is_met_by(decision) {
+                    correct += 1;
+                }
+                all += 1;
+            }
+        }
+        Self {
+            members: trained.into_iter().map(|(member, _)| member).collect(),
+            oob_accuracy: (all != 0).then(|| Ratio::new(correct, all)),
+        }
+    }
+
+    // CRUD-R: Properties
+
+    pub fn members(&self) -> &[OneLayerNN<N, D, U>] {
+        &self.members
+    }
+    /// Out-of-bag accuracy. `None` if every sample was in every resample.
+    pub fn oob_accuracy(&self) -> Option<Ratio<u64>> {
+        self.oob_accuracy
+    }
+    /// Returns the output decided by the most members. Ties go to the lowest output.
+    ///
+    /// # Panics
+    /// * If the ensemble has no members.
+    pub fn decide_for<I: OutputInt>(&self, input: &PerVec<D>) -> I {
+        self.decide_with(input, |output: I| output)
+    }
+    /// Returns the class decided by the most members, where outputs are mapped to classes with `decode`.
+    ///
+    /// Use it when several outputs mean the same class, so that their votes add up.
+    /// Ties go to the lowest class.
+    ///
+    /// # Panics
+    /// * If the ensemble has no members.
+    pub fn decide_with<I, C, F>(&self, input: &PerVec<D>, decode: F) -> C
+    where
+        I: OutputInt,
+        C: Ord,
+        F: Fn(I) -> C,
+    {
+        Self::plurality_vote(self.members.iter(), input, decode)
+            .expect("Ensemble should have at least one member.")
+    }
+    /// Returns share of samples for which [`Self::decide_for`] meets the expectation.
+    pub fn accuracy_for<'i, II, EI, I, E>(&self, inputs: II, expected: EI) -> Option<Ratio<u64>>
+    where
+        II: IntoIterator<Item = &'i PerVec<D>>,
+        EI: IntoIterator<Item = E>,
+        I: OutputInt,
+        E: IntExpect<ProvidedInt = I>,
+    {
+        let [mut correct, mut all] = [0, 0];
+        for (input, expectation) in inputs.into_iter().zip(expected) {
+            if expectation.is_met_by(self.decide_for::<I>(input)) {
+                correct += 1;
+            }
+            all += 1;
+        }
+        (all != 0).then(|| Ratio::new(correct, all))
+    }
+
+    /// Vote for decoded outputs. Ties go to the lowest class. `None` if there are no voters.
+    fn plurality_vote<'m, I, C, F>(
+        voters: impl Iterator<Item = &'m OneLayerNN<N, D, U>>,
+        input: &PerVec<D>,
+        decode: F,
+    ) -> Option<C>
+    where
+        U: 'm,
+        I: OutputInt,
+        C: Ord,
+        F: Fn(I) -> C,
+    {
+        let mut votes: BTreeMap<C, usize> = BTreeMap::new();
+        for voter in voters {
+            *votes.entry(decode(voter.decide_for(input))).or_insert(0) += 1;
+        }
+        votes
+            .into_iter()
+            // First of the greatest, i.e. the lowest class among tied ones.
+            .min_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs))
+            .map(|(class, _)| class)
+    }
+}
+
+impl<const N: usize, const D: usize, U> BaggingEnsemble<N, D, U>
+where
+    U: LinearNeuron<D>,
+{
+    /// Returns decision by averaged scores. Each bit is set iff members' `w·x - theta` for it averages to non-negative.
+    ///
+    /// Unlike [`Self::decide_for`], confident members outweigh hesitant ones.
+    pub fn decide_by_scores_for<I: OutputInt>(&self, input: &PerVec<D>) -> I {
+        let mut score_sums = [0.; N];
+        for member in &self.members {
+            for (sum, score) in score_sums.iter_mut().zip(member.net_inputs_for(input)) {
+                *sum += score;
+            }
+        }
+        score_sums
+            .iter()
+            .enumerate()
+            .filter(|&(_, &sum)| sum >= 0.)
+            .fold(I::zero(), |acc, (bit, _)| acc | (I::one() << bit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Network deciding `output` for non-negative inputs.
+    fn constant_member(output: u8) -> OneLayerNN<2, 1> {
+        let mut network = OneLayerNN::<2, 1>::default();
+        for (bit, perceptron) in network.neurons_mut().iter_mut().enumerate() {
+            perceptron.set_theta(if output & (1 << bit) != 0 { 0. } else { 1. });
+        }
+        network
+    }
+
+    fn ensemble_of(outputs: &[u8]) -> BaggingEnsemble<2, 1> {
+        BaggingEnsemble {
+            members: outputs
+                .iter()
+                .map(|&output| constant_member(output))
+                .collect(),
+            oob_accuracy: None,
+        }
+    }
+
+    /// Two classes told apart by the sign of the input.
+    fn samples() -> (Vec<PerVec<1>>, Vec<u8>) {
+        (-5..=5)
+            .filter(|&x| x != 0)
+            .map(|x| (PerVec::<1>::new(x as f32), if x > 0 { 0b11 } else { 0b00 }))
+            .unzip()
+    }
+
+    #[test]
+    fn most_decided_output_wins_and_ties_go_to_lowest() {
+        let input = PerVec::<1>::zeros();
+        for (outputs, expected) in [
+            (&[0b11, 0b00][..], 0b00u8),
+            (&[0b00, 0b11, 0b11], 0b11),
+            // Bitwise majority would make up 0b11, which no member decided.
+            (&[0b01, 0b10, 0b00], 0b00),
+            (&[0b10, 0b01, 0b01], 0b01),
+        ] {
+            let decision = ensemble_of(outputs).decide_for::<u8>(&input);
+            assert_eq!(decision, expected, "{outputs:?}");
+        }
+    }
+
+    #[test]
+    fn votes_for_same_class_add_up() {
+        let input = PerVec::<1>::zeros();
+        let ensemble = ensemble_of(&[0b11, 0b01, 0b11, 0b10, 0b11, 0b10, 0b01]);
+        assert_eq!(ensemble.decide_for::<u8>(&input), 0b11);
+        // Both one-bit codes mean class 1.
+        let class = ensemble.decide_with(&input, |output: u8| output.count_ones());
+        assert_eq!(class, 1);
+    }
+
+    #[test]
+    fn fitting_is_reproducible_and_learns_separable_data() {
+        let (inputs, expecteds) = samples();
+        let fit = || BaggingEnsemble::<2, 1>::fit::<u8, _>(&inputs, &expecteds, 5, 5, 42);
+        let ensemble = fit();
+        assert_eq!(ensemble.members().len(), 5);
+        assert_eq!(
+            serde_json::to_string(&ensemble).unwrap(),
+            serde_json::to_string(&fit()).unwrap()
+        );
+        assert!(ensemble.oob_accuracy().is_some());
+        assert_eq!(
+            ensemble.accuracy_for(&inputs, expecteds.iter().copied()),
+            Some(Ratio::from_integer(1))
+        );
+        for input in &inputs {
+            assert_eq!(
+                ensemble.decide_by_scores_for::<u8>(input),
+                ensemble.decide_for::<u8>(input)
+            );
+        }
+    }
+
+    #[test]
+    fn persisted_ensemble_decides_the_same() -> anyhow::Result<()> {
+        let (inputs, expecteds) = samples();
+        let ensemble = BaggingEnsemble::<2, 1>::fit::<u8, _>(&inputs, &expecteds, 3, 5, 1);
+        let path =
+            std::env::temp_dir().join(format!("perc_ic-bagging-{}.json", std::process::id()));
+        crate::persist::save(&ensemble, &path)?;
+        let loaded: BaggingEnsemble<2, 1> = crate::persist::load(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(loaded.oob_accuracy(), ensemble.oob_accuracy());
+        for input in &inputs {
+            assert_eq!(
+                loaded.decide_for::<u8>(input),
+                ensemble.decide_for::<u8>(input)
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+//! Per-epoch records of training, exportable as CSV and as SVG line charts.
+
+use std::{fmt::Write as _, fs::File, io::Write, path::Path, time::Duration};
+
+use anyhow::Context;
+use num_rational::Ratio;
+
+use crate::perceptron::PerFloat;
+
+/// Outcome of one training epoch.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct EpochRecord {
+    /// Number of the epoch, starting at 1.
+    pub epoch: u64,
+    pub accuracy: Option<Ratio<u64>>,
+    /// Weight updates made during the epoch, counted per unit. See [`crate::util::SampleOutcome`].
+    pub updates: u64,
+    /// Regularization penalty at the end of the epoch. Zero without regularization.
+    pub penalty: PerFloat,
+    pub elapsed: Duration,
+}
+
+/// Records of all epochs of a training run, as returned by e.g. [`crate::Neuron::fit_recorded`].
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TrainingHistory {
+    records: Vec<EpochRecord>,
+}
+
+impl TrainingHistory {
+    // CRUD-R: Properties
+
+    pub fn records(&self) -> &[EpochRecord] {
+        &self.records
+    }
+    /// Accuracy of the last epoch.
+    pub fn last_score(&self) -> Option<Ratio<u64>> {
+        self.records.last().and_then(|record| record.accuracy)
+    }
+
+    // CRUD-U: Recording
+
+    pub fn push(&mut self, record: EpochRecord) {
+        self.records.push(record);
+    }
+
+    // CRUD-R: Exporting
+
+    /// Writes records as CSV with header `epoch,accuracy,updates,penalty,elapsed_ms`. Accuracy is a decimal fraction.
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn write_csv<W: Write>(&self, writer: W) -> anyhow::Result<()> {
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        csv_writer.write_record(["epoch", "accuracy", "updates", "penalty", "elapsed_ms"])?;
+        for record in &self.records {
+            csv_writer
+                .write_record([
+                    record.epoch.to_string(),
+                    record
+                        .accuracy
+                        .map(ratio_to_f64)
+                        .map_or_else(String::new, |a| a.to_string()),
+                    record.updates.to_string(),
+                    record.penalty.to_string(),
+                    (record.elapsed.as_secs_f64() * 1000.).to_string(),
+                ])
+                .with_context(|| format!("Failed to write record of epoch {}.", record.epoch))?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+    /// Writes records into a newly created CSV file.
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn write_csv_path(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        (|| self.write_csv(File::create(path)?))().with_context(|| {
+            format!(
+                "Failed to write training history to \"{}\".",
+                path.display()
+            )
+        })
+    }
+    /// Renders accuracy and updates per epoch as an SVG line chart. See [`chart_svg`].
+    pub fn to_svg(&self) -> String {
+        chart_svg(&[("training", self)])
+    }
+    /// Returned error reports what failed, not only why.
+    pub fn write_svg_path(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        write_chart_svg(path, &[("training", self)])
+    }
+}
+
+/// Series colors. Series beyond their count reuse them cyclically.
+const PALETTE: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b",
+];
+const CHART_WIDTH: f64 = 640.;
+const PANEL_HEIGHT: f64 = 220.;
+const MARGIN: f64 = 48.;
+
+/// Renders named histories as one SVG chart, e.g. to compare learning rates or algorithms.
+///
+/// The upper panel shows accuracy and the lower one weight updates, both per epoch.
+pub fn chart_svg(series: &[(&str, &TrainingHistory)]) -> String {
+    let max_epoch = series
+        .iter()
+        .flat_map(|(_, history)| history.records.last())
+        .map(|record| record.epoch)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let max_updates = series
+        .iter()
+        .flat_map(|(_, history)| &history.records)
+        .map(|record| record.updates)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let height = 2. * PANEL_HEIGHT + 3. * MARGIN;
+    let plot_width = CHART_WIDTH - 2. * MARGIN;
+    let panel_tops = [MARGIN, 2. * MARGIN + PANEL_HEIGHT];
+
+    let mut svg = String::new();
+    // Writing into `String` can't fail.
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{CHART_WIDTH}" height="{height}" viewBox="0 0 {CHART_WIDTH} {height}" font-family="sans-serif" font-size="12">"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="{CHART_WIDTH}" height="{height}" fill="white"/>"#
+    );
+    for (top, title, max) in [
+        (panel_tops[0], "accuracy", 1.),
+        (panel_tops[1], "updates", max_updates),
+    ] {
+        let bottom = top + PANEL_HEIGHT;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{MARGIN}" y="{top}" width="{plot_width}" height="{PANEL_HEIGHT}" fill="none" stroke="black"/>"#
+        );
+        let _ = writeln!(svg, r#"<text x="{MARGIN}" y="{}">{title}</text>"#, top - 6.);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{top}" text-anchor="end" dominant-baseline="middle">{max}</text>"#,
+            MARGIN - 4.
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{bottom}" text-anchor="end" dominant-baseline="middle">0</text>"#,
+            MARGIN - 4.
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="end">epoch {max_epoch}</text>"#,
+            MARGIN + plot_width,
+            bottom + 16.
+        );
+    }
+    for (idx, (name, history)) in series.iter().enumerate() {
+        let color = PALETTE[idx % PALETTE.len()];
+        let x = |epoch: u64| MARGIN + epoch as f64 / max_epoch * plot_width;
+        let accuracy_points: Vec<String> = history
+            .records
+            .iter()
+            .filter_map(|record| Some((record.epoch, ratio_to_f64(record.accuracy?))))
+            .map(|(epoch, accuracy)| {
+                format!(
+                    "{},{}",
+                    x(epoch),
+                    panel_tops[0] + (1. - accuracy) * PANEL_HEIGHT
+                )
+            })
+            .collect();
+        let mistake_points: Vec<String> = history
+            .records
+            .iter()
+            .map(|record| {
+                let y = panel_tops[1] + (1. - record.updates as f64 / max_updates) * PANEL_HEIGHT;
+                format!("{},{y}", x(record.epoch))
+            })
+            .collect();
+        for points in [accuracy_points, mistake_points] {
+            let _ = writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="1.5"/>"#,
+                points.join(" ")
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" fill="{color}">{}</text>"#,
+            MARGIN + 8.,
+            panel_tops[0] + 16. * (idx + 1) as f64,
+            name.replace('&', "&amp;").replace('<', "&lt;")
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Writes [`chart_svg`] of `series` into a newly created file.
+///
+/// Returned error reports what failed, not only why.
+pub fn write_chart_svg(
+    path: impl AsRef<Path>,
+    series: &[(&str, &TrainingHistory)],
+) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    std::fs::write(path, chart_svg(series))
+        .with_context(|| format!("Failed to write training chart to \"{}\".", path.display()))
+}
+
+pub(crate) fn ratio_to_f64(ratio: Ratio<u64>) -> f64 {
+    *ratio.numer() as f64 / *ratio.denom() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> TrainingHistory {
+        let mut history = TrainingHistory::default();
+        for (epoch, accuracy, updates, penalty) in
+            [(1, Some(Ratio::new(1, 4)), 6, 0.5), (2, None, 0, 0.25)]
+        {
+            history.push(EpochRecord {
+                epoch,
+                accuracy,
+                updates,
+                penalty,
+                elapsed: Duration::from_micros(1500),
+            });
+        }
+        history
+    }
+
+    #[test]
+    fn csv_has_a_row_per_epoch() -> anyhow::Result<()> {
+        let mut csv = Vec::new();
+        history().write_csv(&mut csv)?;
+        assert_eq!(
+            String::from_utf8(csv)?,
+            "epoch,accuracy,updates,penalty,elapsed_ms\n1,0.25,6,0.5,1.5\n2,,0,0.25,1.5\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn last_score_is_of_the_last_epoch() {
+        let mut history = history();
+        assert_eq!(history.last_score(), None);
+        history.records.pop();
+        assert_eq!(history.last_score(), Some(Ratio::new(1, 4)));
+        assert_eq!(TrainingHistory::default().last_score(), None);
+    }
+
+    #[test]
+    fn chart_has_both_panels_per_series_with_escaped_names() {
+        let history = history();
+        let svg = chart_svg(&[("a<b", &history), ("c&d", &TrainingHistory::default())]);
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<polyline").count(), 4);
+        assert!(svg.contains(">a&lt;b</text>") && svg.contains(">c&amp;d</text>"));
+        assert!(svg.contains(">updates</text>") && svg.contains(">epoch 2</text>"));
+        assert!(svg.contains(&format!(r#"stroke="{}""#, PALETTE[1])));
+    }
+}
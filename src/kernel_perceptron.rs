@@ -0,0 +1,200 @@
+use crate::{
+    perceptron::{PerFloat, PerVec},
+    util::{BoolExpect, BoolExpectation, Correctness, SampleOutcome},
+};
+
+pub use kernel::{Kernel, LinearKernel, PolynomialKernel, RbfKernel};
+pub mod kernel;
+
+/// Sample that the perceptron made mistakes on.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SupportSample<const D: usize> {
+    pub input: PerVec<D>,
+    pub expectation: bool,
+    pub mistakes: u32,
+    /// Sum of sample weights over the mistakes.
+    pub weight: PerFloat,
+}
+
+/// Perceptron in dual form.
+///
+/// Instead of weights, it remembers samples it made mistakes on together with the mistake counts.
+/// Decision is based on [`Kernel`] similarity to these samples. With nonlinear kernels this separates classes that no hyperplane can.
+/// Accuracy and multi-sample training come from [`crate::Neuron`].
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct KernelPerceptron<const D: usize, K = RbfKernel> {
+    kernel: K,
+    support: Vec<SupportSample<D>>,
+    theta: PerFloat,
+}
+
+impl<const D: usize, K: Kernel> KernelPerceptron<D, K> {
+    pub const ALPHA: PerFloat = 0.1;
+
+    // CRUD-C: Constructors
+
+    pub fn new(kernel: K) -> Self {
+        Self {
+            kernel,
+            support: Vec::new(),
+            theta: 0.,
+        }
+    }
+
+    // CRUD-R: Properties
+
+    pub fn kernel(&self) -> &K {
+        &self.kernel
+    }
+    pub fn support(&self) -> &[SupportSample<D>] {
+        &self.support
+    }
+    /// Value compared against theta. Counterpart of `w·x` in the primal form.
+    pub fn net_input(&self, input: &PerVec<D>) -> PerFloat {
+        self.support
+            .iter()
+            .map(|sample| {
+                let sign = if sample.expectation { 1. } else { -1. };
+                sign * Self::ALPHA * sample.weight * self.kernel.eval(&sample.input, input)
+            })
+            .sum()
+    }
+    /// Activation function.
+    pub fn activation(&self, value: PerFloat) -> bool {
+        value >= self.theta
+    }
+    /// Returns decision for given [`input`].
+    pub fn decide_for(&self, input: &PerVec<D>) -> bool {
+        self.activation(self.net_input(input))
+    }
+
+    // CRUD-U: Training [`self`].
+
+    pub fn train_on_sample<E>(&mut self, input: &PerVec<D>, expectation: E) -> SampleOutcome
+    where
+        E: BoolExpect,
+    {
+        self.train_on_weighted_sample(input, expectation, 1.)
+    }
+    /// Like [`Self::train_on_sample`], but scales the update step by `weight`.
+    pub fn train_on_weighted_sample<E>(
+        &mut self,
+        input: &PerVec<D>,
+        expectation: E,
+        weight: PerFloat,
+    ) -> SampleOutcome
+    where
+        E: BoolExpect,
+    {
+        let BoolExpectation::Expect(expectation) = expectation.expectation() else {
+            // No expectation ==> nothing to do ==> everything is ok
+            return SampleOutcome::new(Correctness::Correct, false);
+        };
+        if expectation == self.decide_for(input) {
+            // Correct, no need to improve
+            return SampleOutcome::new(Correctness::Correct, false);
+        }
+        // Update self by remembering the mistake.
+        match self
+            .support
+            .iter_mut()
+            .find(|sample| sample.expectation == expectation && sample.input == *input)
+        {
+            Some(sample) => {
+                sample.mistakes += 1;
+                sample.weight += weight;
+            }
+            None => self.support.push(SupportSample {
+                input: *input,
+                expectation,
+                mistakes: 1,
+                weight,
+            }),
+        }
+        let translation_dir = if expectation { 1. } else { -1. };
+        self.theta -= translation_dir * Self::ALPHA * weight; // Input is -1.
+        SampleOutcome::new(Correctness::Incorrect, true) // BUT improved
+    }
+}
+
+impl<const D: usize, K: Kernel> crate::Neuron<PerVec<D>> for KernelPerceptron<D, K> {
+    fn decide_for(&self, input: &PerVec<D>) -> bool {
+        self.decide_for(input)
+    }
+    fn train_on_weighted_sample<E: BoolExpect>(
+        &mut self,
+        input: &PerVec<D>,
+        expectation: E,
+        weight: PerFloat,
+    ) -> SampleOutcome {
+        self.train_on_weighted_sample(input, expectation, weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_rational::Ratio;
+
+    use super::*;
+    use crate::Neuron;
+
+    /// XOR of signs, which no hyperplane separates.
+    fn xor() -> ([PerVec<2>; 4], [bool; 4]) {
+        (
+            [[-1., -1.], [-1., 1.], [1., -1.], [1., 1.]].map(PerVec::from),
+            [false, true, true, false],
+        )
+    }
+
+    #[test]
+    fn rbf_kernel_separates_xor() {
+        let (inputs, expecteds) = xor();
+        let mut perceptron = KernelPerceptron::<2>::default();
+        for _ in 0..10 {
+            perceptron.train_on(&inputs, expecteds);
+        }
+        assert_eq!(
+            perceptron.accuracy_for(&inputs, expecteds),
+            Some(Ratio::from_integer(1))
+        );
+        assert!(perceptron.support().len() <= inputs.len());
+    }
+
+    #[test]
+    fn linear_kernel_doesnt_separate_xor() {
+        let (inputs, expecteds) = xor();
+        let mut perceptron = KernelPerceptron::<2, _>::new(LinearKernel);
+        for _ in 0..10 {
+            perceptron.train_on(&inputs, expecteds);
+        }
+        assert_ne!(
+            perceptron.accuracy_for(&inputs, expecteds),
+            Some(Ratio::from_integer(1))
+        );
+    }
+
+    #[test]
+    fn repeated_mistakes_add_up_in_one_support_sample() {
+        let input = PerVec::<2>::new(1., 0.);
+        let mut perceptron = KernelPerceptron::<2>::default();
+        assert_eq!(
+            perceptron.train_on_weighted_sample(&input, false, 2.),
+            SampleOutcome::new(Correctness::Incorrect, true)
+        );
+        assert_eq!(perceptron.theta, 0.2);
+        // Net input -0.2 < theta 0.2, so flipping the expectation is a mistake again.
+        assert_eq!(
+            perceptron.train_on_sample(&input, true),
+            SampleOutcome::new(Correctness::Incorrect, true)
+        );
+        // Net input 0.1 - 0.2 < theta 0.1, so `true` is missed once more.
+        assert_eq!(
+            perceptron.train_on_sample(&input, true),
+            SampleOutcome::new(Correctness::Incorrect, true)
+        );
+        let support = perceptron.support();
+        assert_eq!(support.len(), 2);
+        assert_eq!((support[1].mistakes, support[1].weight), (2, 2.));
+        assert!(perceptron.decide_for(&input));
+    }
+}
@@ -0,0 +1,81 @@
+//! Kernels, i.e. dot products in implicit feature spaces.
+
+use crate::perceptron::{PerFloat, PerVec};
+
+pub trait Kernel {
+    fn eval<const D: usize>(&self, lhs: &PerVec<D>, rhs: &PerVec<D>) -> PerFloat;
+}
+
+/// `x·y`
+///
+/// Makes [`super::KernelPerceptron`] equivalent to [`crate::Perceptron`].
+#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct LinearKernel;
+
+impl Kernel for LinearKernel {
+    fn eval<const D: usize>(&self, lhs: &PerVec<D>, rhs: &PerVec<D>) -> PerFloat {
+        crate::util::sf32_vec::dot(lhs, rhs)
+    }
+}
+
+/// `(gamma * x·y + coef0)^degree`
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PolynomialKernel {
+    pub degree: i32,
+    pub gamma: PerFloat,
+    pub coef0: PerFloat,
+}
+
+impl Default for PolynomialKernel {
+    fn default() -> Self {
+        Self {
+            degree: 3,
+            gamma: 1.,
+            coef0: 1.,
+        }
+    }
+}
+
+impl Kernel for PolynomialKernel {
+    fn eval<const D: usize>(&self, lhs: &PerVec<D>, rhs: &PerVec<D>) -> PerFloat {
+        (self.gamma * crate::util::sf32_vec::dot(lhs, rhs) + self.coef0).powi(self.degree)
+    }
+}
+
+/// `exp(-gamma * |x - y|^2)`
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RbfKernel {
+    pub gamma: PerFloat,
+}
+
+impl Default for RbfKernel {
+    fn default() -> Self {
+        Self { gamma: 1. }
+    }
+}
+
+impl Kernel for RbfKernel {
+    fn eval<const D: usize>(&self, lhs: &PerVec<D>, rhs: &PerVec<D>) -> PerFloat {
+        (-self.gamma * (lhs - rhs).norm_squared()).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kernels_evaluate_their_formulas() {
+        let [x, y] = [PerVec::<2>::new(1., 2.), PerVec::<2>::new(3., -1.)];
+        assert_eq!(LinearKernel.eval(&x, &y), 1.);
+        assert_eq!(PolynomialKernel::default().eval(&x, &y), 8.);
+        let quadratic = PolynomialKernel {
+            degree: 2,
+            gamma: 0.5,
+            coef0: 0.,
+        };
+        assert_eq!(quadratic.eval(&x, &y), 0.25);
+        assert_eq!(RbfKernel::default().eval(&x, &x), 1.);
+        assert!((RbfKernel { gamma: 0.1 }.eval(&x, &y) - (-1.3f32).exp()).abs() < 1e-6);
+    }
+}
@@ -1,22 +1,55 @@
 use core::panic;
 
-use anyhow::Context;
 pub use nn::one_layer::OneLayerNN;
 pub mod nn;
 
 pub use perceptron::Perceptron;
 pub mod perceptron;
 
-#[allow(deprecated)]
+pub use adaline::Adaline;
+pub mod adaline;
+
+pub use kernel_perceptron::KernelPerceptron;
+pub mod kernel_perceptron;
+
+pub use neuron::{LinearNeuron, Neuron};
+pub mod neuron;
+
+pub use regularization::Regularization;
+pub mod regularization;
+
+pub use sparse_perceptron::SparsePerceptron;
+pub mod sparse_perceptron;
+
+pub use stopping::StoppingCriteria;
+pub mod stopping;
+
 pub use app::{
     args::AppArgs,
     cfg::{app_cfg, AppCfg, APP_CFG},
 };
-#[deprecated]
 pub mod app;
 
+pub mod read;
+
 pub mod util;
 
+pub use data::{Dataset, PartialLabel};
+pub mod data;
+
+pub mod checkpoint;
+pub mod config;
+pub mod ensemble;
+pub mod history;
+pub mod multi_label;
+pub mod online;
+pub mod output;
+pub mod persist;
+pub mod plot;
+pub mod reject;
+pub mod repl;
+pub mod serve;
+
 use class_expectation::ClassificationExpectation;
 mod class_expectation;
 
@@ -25,35 +58,94 @@ mod class_expectation;
 /// This function is non-deterministic. Meaning it can return different classifiers on each run.
 /// Optimizations are suboptimal. If you are lucky, a random shuffle will order training data optimaly making the training more effective. Thus, returning a classifier with high accuracy. This randomness is expected to have small impact on huge data sets.
 pub fn create_classifier(
-    mut classified_irises: Vec<ic::ClassifiedIris>,
+    classified_irises: Vec<ic::ClassifiedIris>,
 ) -> anyhow::Result<impl Fn(ic::UnclassifiedIris) -> ic::ClassifiedIris> {
+    create_partial_classifier(
+        classified_irises
+            .into_iter()
+            .map(|ci| (ci.parameters, ci.classification.into()))
+            .collect(),
+    )
+}
+
+/// Like [`create_classifier`], but learns also from irises whose species is known partially or not at all.
+///
+/// Such irises train only output bits their possible species agree on.
+/// Irises with [`PartialLabel::Reject`] label train the output reserved for them, see [`prediction_to_judgement`].
+///
+/// # Errors
+/// * If training data is empty.
+pub fn create_partial_classifier(
+    irises: Vec<(ic::UnclassifiedIris, PartialLabel<ic::IrisSpecies>)>,
+) -> anyhow::Result<impl Fn(ic::UnclassifiedIris) -> ic::ClassifiedIris> {
+    let nn = train_iris_network(irises)?;
+    Ok(move |unclassified_iris| classify_iris(&nn, unclassified_iris))
+}
+
+/// Network used by iris classifiers. Its 2-bit outputs are converted to species by [`classify_iris`].
+pub type IrisNetwork = OneLayerNN<2, 4>;
+
+/// Trains the network behind [`create_partial_classifier`].
+///
+/// Unlike the classifier, the network exposes margins, e.g. for [`reject`]ing uncertain irises.
+pub fn train_iris_network(
+    irises: Vec<(ic::UnclassifiedIris, PartialLabel<ic::IrisSpecies>)>,
+) -> anyhow::Result<IrisNetwork> {
+    train_iris_network_with(irises, &config::TrainingConfig::default())
+}
+
+/// Like [`train_iris_network`], but set up by `config`. Its data sources aren't read.
+///
+/// # Errors
+/// * If `config` is invalid.
+/// * If training data is empty.
+pub fn train_iris_network_with(
+    mut irises: Vec<(ic::UnclassifiedIris, PartialLabel<ic::IrisSpecies>)>,
+    config: &config::TrainingConfig,
+) -> anyhow::Result<IrisNetwork> {
     use rand::prelude::*;
-    classified_irises.shuffle(&mut thread_rng());
+    config.validate()?;
+    if irises.is_empty() {
+        anyhow::bail!("Provided training data is an empty table.");
+    }
+    if config.preprocessing.shuffle {
+        match config.preprocessing.seed {
+            Some(seed) => irises.shuffle(&mut rand_chacha::ChaCha8Rng::seed_from_u64(seed)),
+            None => irises.shuffle(&mut thread_rng()),
+        }
+    }
 
-    let mut nn = crate::OneLayerNN::<2, 4>::default();
-    nn.fit_to::<_, _, _, ClassificationExpectation>(
-        classified_irises
+    tracing::info!(
+        irises = irises.len(),
+        seed = config.preprocessing.seed,
+        "Training iris network."
+    );
+    let mut nn = config.network();
+    nn.fit::<_, _, _, PartialLabel<ClassificationExpectation>>(
+        irises.iter().map(|(iris, _)| iris.as_na_svec()),
+        irises
             .iter()
-            .map(|ci| ci.parameters.as_na_svec()),
-        classified_irises
-            .iter()
-            .map(|ci| ci.classification)
-            .map(ClassificationExpectation::from),
-        10,
-    )
-    .context("Provided training data is an empty table.")?;
-
-    Ok(
-        move |unclassified_iris: ic::UnclassifiedIris| -> ic::ClassifiedIris {
-            let prediction = nn.decide_for(unclassified_iris.as_na_svec());
-            let classification = prediction_to_classification(prediction);
-            ic::ClassifiedIris::new(unclassified_iris, classification)
-        },
-    )
+            .map(|(_, label)| label.clone().map(ClassificationExpectation::from)),
+        config.stopping.criteria()?,
+        Some(num_traits::zero()),
+    );
+    Ok(nn)
+}
+
+/// Classifies `unclassified_iris` with a network trained by [`train_iris_network`].
+pub fn classify_iris(
+    nn: &IrisNetwork,
+    unclassified_iris: ic::UnclassifiedIris,
+) -> ic::ClassifiedIris {
+    let prediction = nn.decide_for(unclassified_iris.as_na_svec());
+    let classification = prediction_to_classification(prediction);
+    ic::ClassifiedIris::new(unclassified_iris, classification)
 }
 
 /// Converts neural network's prediction into a valid iris classification.
-fn prediction_to_classification(prediction: u8) -> ic::IrisSpecies {
+///
+/// The reject output counts as Setosa. [`prediction_to_judgement`] tells it apart.
+pub fn prediction_to_classification(prediction: u8) -> ic::IrisSpecies {
     use ic::IrisSpecies as S;
     match prediction {
         0b_00 => S::Versicolor,
@@ -62,3 +154,9 @@ fn prediction_to_classification(prediction: u8) -> ic::IrisSpecies {
         _ => panic!("Logic error: function didn't expect `prediction` > 3 ."),
     }
 }
+
+/// Like [`prediction_to_classification`], but `None` for the reject output `0b_11`, learned from [`PartialLabel::Reject`] irises.
+pub fn prediction_to_judgement(prediction: u8) -> Option<ic::IrisSpecies> {
+    (prediction != ClassificationExpectation::REJECT_OUTPUT)
+        .then(|| prediction_to_classification(prediction))
+}
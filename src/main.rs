@@ -1,26 +1,151 @@
-use ic::{read, ClassifiedIris};
+use perc_ic::{
+    app::args::{Command, PlotArgs, ReplArgs, ServeArgs},
+    config::TrainedModel,
+    output::{self, OutputOptions, Prediction},
+    read, reject, IrisNetwork, PartialLabel,
+};
 
 fn main() -> anyhow::Result<()> {
-    let app_args: ic::AppArgs = clap::Parser::parse();
-    ic::APP_CFG
-        .set(ic::AppCfg::new(app_args))
+    let app_args: perc_ic::AppArgs = clap::Parser::parse();
+    perc_ic::app::init_logging(&app_args)?;
+    let app_cfg = perc_ic::AppCfg::new(app_args)?;
+    app_cfg.validate()?;
+    perc_ic::APP_CFG
+        .set(app_cfg)
         .expect("This should be the only app config initialization.");
 
+    if let Some(Command::Serve(serve_args)) = &perc_ic::app_cfg().command {
+        return serve(serve_args);
+    }
+    if let Some(Command::Repl(repl_args)) = &perc_ic::app_cfg().command {
+        return repl(repl_args);
+    }
+
     // Reading iris data.
     let training_irises = read::training_irises()?;
     // Creating classifier using the classified data.
-    let iris_classifier = perc_ic::create_classifier(training_irises)?;
-    if ic::app_cfg().run_accuracy_measure {
-        ic::app::run_accuracy_measure(&iris_classifier)?;
+    let iris_network =
+        perc_ic::train_iris_network_with(training_irises.clone(), &perc_ic::app_cfg().training)?;
+    if let Some(path) = &perc_ic::app_cfg().save_model {
+        TrainedModel {
+            network: iris_network.clone(),
+            config: perc_ic::app_cfg().training.clone(),
+        }
+        .save(path)?;
+    }
+    if let Some(Command::Plot(plot_args)) = &perc_ic::app_cfg().command {
+        return plot(&iris_network, &training_irises, plot_args);
+    }
+    let iris_classifier =
+        |unclassified_iris| perc_ic::classify_iris(&iris_network, unclassified_iris);
+    if perc_ic::app_cfg().run_accuracy_measure {
+        perc_ic::app::run_accuracy_measure(
+            iris_classifier,
+            &perc_ic::app_cfg().training.data.test,
+        )?;
+    }
+    if perc_ic::app_cfg().reject_curve {
+        let testing_irises = read::testing_irises()?;
+        let curve = reject::coverage_curve(&iris_network, &testing_irises);
+        eprintln!("{}", tabled::Table::new(curve));
     }
     let user_irises = read::user_irises()?;
 
     // Classifying all unclassified irises using classifier.
-    let now_classified_irises: Vec<ClassifiedIris> =
-        ic::classify_irises(&iris_classifier, user_irises);
+    let cfg = perc_ic::app_cfg();
+    let predictions: Vec<Prediction> = user_irises
+        .into_iter()
+        .enumerate()
+        .map(|(index, iris)| Prediction {
+            index,
+            iris,
+            classification: match cfg.reject_threshold {
+                Some(threshold) => reject::classify_or_reject(&iris_network, &iris, threshold),
+                None => reject::classify(&iris_network, &iris),
+            },
+            scores: cfg
+                .scores
+                .then(|| reject::class_scores(&iris_network, &iris)),
+        })
+        .collect();
     // Displaying the classifications made for user.
-    let table_with_classified = tabled::Table::new(now_classified_irises);
-    print!("{}", table_with_classified);
+    let options = OutputOptions {
+        format: cfg.format,
+        with_index: cfg.row_index,
+        with_scores: cfg.scores,
+        separator: cfg.training.data.separator.into(),
+    };
+    output::write_predictions(output::create(&cfg.output)?, &predictions, options)?;
 
     Ok(())
 }
+
+/// Draws `iris_network` over training irises of known species.
+fn plot(
+    iris_network: &IrisNetwork,
+    training_irises: &[(ic::UnclassifiedIris, PartialLabel<ic::IrisSpecies>)],
+    plot_args: &PlotArgs,
+) -> anyhow::Result<()> {
+    use ic::IrisSpecies as S;
+    const PARAM_NAMES: [&str; 4] = ["sepal length", "sepal width", "petal length", "petal width"];
+
+    let samples: Vec<_> = training_irises
+        .iter()
+        .filter_map(|(iris, label)| match label {
+            PartialLabel::Known(species) => Some((*iris.as_na_svec(), u8::from(*species).into())),
+            _ => None,
+        })
+        .collect();
+    let axis_name = |dim: usize| PARAM_NAMES.get(dim).copied().unwrap_or_default();
+    perc_ic::plot::Plot::new(
+        iris_network,
+        &samples,
+        [plot_args.x, plot_args.y],
+        [plot_args.width, plot_args.height],
+        |prediction| u8::from(perc_ic::prediction_to_classification(prediction)).into(),
+    )?
+    .with_axis_names(axis_name(plot_args.x), axis_name(plot_args.y))
+    .with_class_names(
+        [S::Setosa, S::Versicolor, S::Virginica]
+            .map(|species| species.to_string())
+            .to_vec(),
+    )
+    .write(&plot_args.output)
+}
+
+/// Serves a saved iris network until the process is killed.
+fn serve(serve_args: &ServeArgs) -> anyhow::Result<()> {
+    let model = TrainedModel::load(&serve_args.model)?;
+    perc_ic::serve::Server::new(model.network, serve_args.model.display().to_string())
+        .with_config(model.config)
+        .with_reject_threshold(perc_ic::app_cfg().reject_threshold)
+        .run(&serve_args.addr, serve_args.workers)
+}
+
+/// Explores a trained or saved iris network with commands from stdin.
+fn repl(repl_args: &ReplArgs) -> anyhow::Result<()> {
+    use std::io::IsTerminal;
+
+    let (model, training_irises) = match &repl_args.model {
+        Some(path) => (TrainedModel::load(path)?, Vec::new()),
+        None => {
+            let training_irises = read::training_irises()?;
+            let config = perc_ic::app_cfg().training.clone();
+            let network = perc_ic::train_iris_network_with(training_irises.clone(), &config)?;
+            (TrainedModel { network, config }, training_irises)
+        }
+    };
+    let stdin = std::io::stdin();
+    let is_interactive = stdin.is_terminal();
+    if is_interactive {
+        eprintln!("Type `help` for commands.");
+    }
+    perc_ic::repl::Repl::new(model.network, training_irises)
+        .with_config(model.config)
+        .with_reject_threshold(perc_ic::app_cfg().reject_threshold)
+        .run(
+            stdin.lock(),
+            std::io::stdout().lock(),
+            is_interactive.then_some("> "),
+        )
+}
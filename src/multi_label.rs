@@ -0,0 +1,233 @@
+//! Multi-label classification, where unit `i` of [`OneLayerNN`] decides whether label `i` applies.
+
+use std::fmt;
+
+use num_rational::Ratio;
+
+use crate::{perceptron::PerVec, util::IntExpect, Neuron, OneLayerNN};
+
+/// Labels of a sample. Label `i` applies iff `self.0[i]`.
+///
+/// As an [`IntExpect`], it demands bit `i` of the output to equal label `i`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct LabelSet<const N: usize>(#[serde(with = "crate::util::serde_array")] pub [bool; N]);
+
+impl<const N: usize> LabelSet<N> {
+    // CRUD-C: Constructors
+
+    /// Takes label `i` from bit `i` of `bits`. Labels beyond bit 63 don't apply.
+    pub fn from_bits(bits: u64) -> Self {
+        Self(std::array::from_fn(|idx| {
+            bits.checked_shr(idx as u32)
+                .is_some_and(|shifted| shifted & 1 == 1)
+        }))
+    }
+
+    // CRUD-R: Properties
+
+    /// Packs label `i` into bit `i`.
+    ///
+    /// # Panics
+    /// * If `N > 64`.
+    pub fn to_bits(&self) -> u64 {
+        assert!(N <= 64, "{N} labels don't fit into 64 bits.");
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, &label)| label)
+            .fold(0, |bits, (idx, _)| bits | 1 << idx)
+    }
+}
+
+impl<const N: usize> IntExpect for LabelSet<N> {
+    type ProvidedInt = u64;
+    type BoolExpectation = bool;
+
+    fn is_met_by(&self, value: Self::ProvidedInt) -> bool {
+        *self == Self::from_bits(value)
+    }
+
+    fn bit_expectation(&self, idx: usize) -> Self::BoolExpectation {
+        self.0[idx]
+    }
+}
+
+/// Returns labels decided by `nn` for `input`.
+pub fn decide_labels<const N: usize, const D: usize, U>(
+    nn: &OneLayerNN<N, D, U>,
+    input: &PerVec<D>,
+) -> LabelSet<N>
+where
+    U: Neuron<PerVec<D>> + Send + Sync,
+{
+    LabelSet::from_bits(nn.decide_for(input))
+}
+
+/// Confusion counts of one label.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LabelScore {
+    pub true_positives: u64,
+    pub false_positives: u64,
+    pub false_negatives: u64,
+    pub true_negatives: u64,
+}
+
+impl LabelScore {
+    pub fn precision(&self) -> Option<Ratio<u64>> {
+        let predicted = self.true_positives + self.false_positives;
+        (predicted != 0).then(|| Ratio::new(self.true_positives, predicted))
+    }
+    pub fn recall(&self) -> Option<Ratio<u64>> {
+        let actual = self.true_positives + self.false_negatives;
+        (actual != 0).then(|| Ratio::new(self.true_positives, actual))
+    }
+    /// Harmonic mean of precision and recall. `None` if the label was neither decided nor expected.
+    pub fn f1(&self) -> Option<Ratio<u64>> {
+        let denominator = 2 * self.true_positives + self.false_positives + self.false_negatives;
+        (denominator != 0).then(|| Ratio::new(2 * self.true_positives, denominator))
+    }
+}
+
+/// Quality of multi-label decisions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MultiLabelReport<const N: usize> {
+    /// Share of labels decided wrong, over all samples and labels.
+    pub hamming_loss: Ratio<u64>,
+    /// Share of samples with all labels decided right.
+    pub subset_accuracy: Ratio<u64>,
+    pub labels: [LabelScore; N],
+}
+
+impl<const N: usize> MultiLabelReport<N> {
+    // CRUD-C: Constructors
+
+    /// Compares `decided` label sets with `expected` ones. `None` if there are no pairs or no labels.
+    pub fn new<DI, EI>(decided: DI, expected: EI) -> Option<Self>
+    where
+        DI: IntoIterator<Item = LabelSet<N>>,
+        EI: IntoIterator<Item = LabelSet<N>>,
+    {
+        let mut labels = [LabelScore::default(); N];
+        let [mut samples, mut exact_matches] = [0_u64, 0];
+        for (decided, expected) in decided.into_iter().zip(expected) {
+            for (score, (&decided, &expected)) in
+                labels.iter_mut().zip(decided.0.iter().zip(&expected.0))
+            {
+                match (decided, expected) {
+                    (true, true) => score.true_positives += 1,
+                    (true, false) => score.false_positives += 1,
+                    (false, true) => score.false_negatives += 1,
+                    (false, false) => score.true_negatives += 1,
+                }
+            }
+            samples += 1;
+            exact_matches += u64::from(decided == expected);
+        }
+        let mistakes: u64 = labels
+            .iter()
+            .map(|score| score.false_positives + score.false_negatives)
+            .sum();
+        (samples != 0 && N != 0).then(|| Self {
+            hamming_loss: Ratio::new(mistakes, samples * N as u64),
+            subset_accuracy: Ratio::new(exact_matches, samples),
+            labels,
+        })
+    }
+    /// Evaluates `nn` on `inputs` labeled by `expected`.
+    pub fn measure<'i, II, EI, U, const D: usize>(
+        nn: &OneLayerNN<N, D, U>,
+        inputs: II,
+        expected: EI,
+    ) -> Option<Self>
+    where
+        II: IntoIterator<Item = &'i PerVec<D>>,
+        EI: IntoIterator<Item = LabelSet<N>>,
+        U: Neuron<PerVec<D>> + Send + Sync,
+    {
+        Self::new(
+            inputs.into_iter().map(|input| decide_labels(nn, input)),
+            expected,
+        )
+    }
+
+    // CRUD-R: Properties
+
+    /// Unweighted mean of F1 scores of labels that have one.
+    pub fn macro_f1(&self) -> Option<f64> {
+        let f1s: Vec<f64> = self
+            .labels
+            .iter()
+            .filter_map(LabelScore::f1)
+            .map(|f1| *f1.numer() as f64 / *f1.denom() as f64)
+            .collect();
+        (!f1s.is_empty()).then(|| f1s.iter().sum::<f64>() / f1s.len() as f64)
+    }
+}
+
+impl<const N: usize> fmt::Display for MultiLabelReport<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Hamming loss: {}", self.hamming_loss)?;
+        writeln!(f, "Subset accuracy: {}", self.subset_accuracy)?;
+        for (idx, score) in self.labels.iter().enumerate() {
+            match score.f1() {
+                Some(f1) => writeln!(f, "F1 of label {idx}: {f1}")?,
+                None => writeln!(f, "F1 of label {idx}: -")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_sets_pack_into_bits() {
+        let labels = LabelSet([true, false, true]);
+        assert_eq!(labels.to_bits(), 0b101);
+        assert_eq!(LabelSet::<3>::from_bits(0b1101), labels);
+        assert!(labels.is_met_by(0b101));
+        assert!(!labels.is_met_by(0b001));
+        assert!(!labels.bit_expectation(1));
+        assert!(!LabelSet::<65>::from_bits(u64::MAX).0[64]);
+    }
+
+    #[test]
+    fn report_counts_label_and_sample_mistakes() {
+        let decided = [LabelSet([true, true]), LabelSet([false, false])];
+        let expected = [LabelSet([true, false]), LabelSet([false, false])];
+        let report = MultiLabelReport::new(decided, expected).unwrap();
+        assert_eq!(report.hamming_loss, Ratio::new(1, 4));
+        assert_eq!(report.subset_accuracy, Ratio::new(1, 2));
+        assert_eq!(
+            report.labels[1],
+            LabelScore {
+                false_positives: 1,
+                true_negatives: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(report.labels[0].f1(), Some(Ratio::from_integer(1)));
+        assert_eq!(report.labels[1].precision(), Some(Ratio::from_integer(0)));
+        assert_eq!(report.labels[1].recall(), None);
+        assert_eq!(report.macro_f1(), Some(0.5));
+        assert_eq!(MultiLabelReport::<2>::new([], []), None);
+    }
+
+    #[test]
+    fn network_learns_independent_labels() {
+        // Label 0 applies to positive first features, label 1 to positive second ones.
+        let samples = [[1., 1.], [1., -1.], [-1., 1.], [-1., -1.]]
+            .map(|[x, y]| (PerVec::<2>::new(x, y), LabelSet([x > 0., y > 0.])));
+        let inputs = samples.map(|(input, _)| input);
+        let expecteds = samples.map(|(_, labels)| labels);
+        let mut nn = OneLayerNN::<2, 2>::default();
+        for _ in 0..10 {
+            nn.train_on(&inputs, expecteds);
+        }
+        let report = MultiLabelReport::measure(&nn, &inputs, expecteds).unwrap();
+        assert_eq!(report.subset_accuracy, Ratio::from_integer(1));
+        assert_eq!(decide_labels(&nn, &inputs[1]), LabelSet([true, false]));
+    }
+}
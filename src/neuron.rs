@@ -0,0 +1,381 @@
+use std::num::NonZeroU64;
+
+use num_rational::Ratio;
+use num_traits::zero;
+
+use crate::{
+    history::TrainingHistory,
+    perceptron::{PerFloat, PerVec},
+    stopping::EpochOutcome,
+    util::{BoolExpect, SampleOutcome},
+    Regularization, StoppingCriteria,
+};
+
+/// Binary unit taking inputs of type `X`, e.g. [`PerVec`] or [`crate::util::SparseVec`].
+///
+/// Units taking `PerVec<D>` can build [`crate::OneLayerNN`], where each unit decides one bit of the output.
+/// Implementing the two required methods is enough to use own unit type in the network.
+pub trait Neuron<X: ?Sized> {
+    // Required
+
+    /// Returns decision for given `input`.
+    fn decide_for(&self, input: &X) -> bool;
+    /// Learns from one sample with update step scaled by `weight`.
+    /// Returns whether the decision was correct before learning and whether weights changed.
+    fn train_on_weighted_sample<E: BoolExpect>(
+        &mut self,
+        input: &X,
+        expectation: E,
+        weight: PerFloat,
+    ) -> SampleOutcome;
+
+    // Provided
+
+    /// Learns from one sample. Returns whether the decision was correct before learning and whether weights changed.
+    fn train_on_sample<E: BoolExpect>(&mut self, input: &X, expectation: E) -> SampleOutcome {
+        self.train_on_weighted_sample(input, expectation, 1.)
+    }
+
+    /// Applies the weight penalty once. Units without explicit weights ignore it.
+    fn regularize(&mut self, _regularization: &Regularization) {}
+    /// Value of the weight penalty for current weights. Zero for units without explicit weights.
+    fn penalty(&self, _regularization: &Regularization) -> PerFloat {
+        0.
+    }
+    /// Returns accuracy this unit has for the given test data.
+    fn accuracy_for<'i, II, EI, E>(&self, inputs: II, expectations: EI) -> Option<Ratio<u64>>
+    where
+        X: 'i,
+        II: IntoIterator<Item = &'i X>,
+        EI: IntoIterator<Item = E>,
+        E: BoolExpect,
+    {
+        let [mut correct, mut all] = [0, 0];
+        for (input, expectation) in inputs.into_iter().zip(expectations) {
+            if expectation.is_met_by(self.decide_for(input)) {
+                correct += 1;
+            }
+            all += 1;
+        }
+        Some(Ratio::new(correct, NonZeroU64::new(all)?.get()))
+    }
+    /// Returns the weighted share of correct decisions. `None` if weights sum to zero.
+    fn weighted_accuracy_for<'i, II, EI, WI, E>(
+        &self,
+        inputs: II,
+        expectations: EI,
+        weights: WI,
+    ) -> Option<f64>
+    where
+        X: 'i,
+        II: IntoIterator<Item = &'i X>,
+        EI: IntoIterator<Item = E>,
+        WI: IntoIterator<Item = PerFloat>,
+        E: BoolExpect,
+    {
+        let [mut correct, mut all] = [0., 0.];
+        for ((input, expectation), weight) in inputs.into_iter().zip(expectations).zip(weights) {
+            if expectation.is_met_by(self.decide_for(input)) {
+                correct += f64::from(weight);
+            }
+            all += f64::from(weight);
+        }
+        (all != 0.).then(|| correct / all)
+    }
+    /// Trains on every sample once. Returns share of samples decided correctly before learning from them.
+    fn train_on<'i, II, EI, E>(&mut self, inputs: II, expecteds: EI) -> Option<Ratio<u64>>
+    where
+        X: 'i,
+        II: IntoIterator<Item = &'i X>,
+        EI: IntoIterator<Item = E>,
+        E: BoolExpect,
+    {
+        self.train_epoch_on(inputs, expecteds).score
+    }
+    /// Like [`Self::train_on`], but also reports the number of weight updates made.
+    ///
+    /// A lone unit isn't regularized, so the reported penalty is zero.
+    fn train_epoch_on<'i, II, EI, E>(&mut self, inputs: II, expecteds: EI) -> EpochOutcome
+    where
+        X: 'i,
+        II: IntoIterator<Item = &'i X>,
+        EI: IntoIterator<Item = E>,
+        E: BoolExpect,
+    {
+        let [mut correct, mut all, mut updates] = [0, 0, 0];
+        for (input, expected) in inputs.into_iter().zip(expecteds) {
+            let outcome = self.train_on_sample(input, expected);
+            if outcome.is_correct() {
+                correct += 1;
+            }
+            updates += outcome.updates;
+            all += 1;
+        }
+        EpochOutcome {
+            score: NonZeroU64::new(all).map(|all| Ratio::new(correct, all.get())),
+            sample_count: all,
+            updates,
+            penalty: 0.,
+        }
+    }
+    /// Trains on every sample once, scaling update steps by sample weights.
+    /// Returns share of samples decided correctly before learning from them.
+    fn train_on_weighted<'i, II, EI, WI, E>(
+        &mut self,
+        inputs: II,
+        expecteds: EI,
+        weights: WI,
+    ) -> Option<Ratio<u64>>
+    where
+        X: 'i,
+        II: IntoIterator<Item = &'i X>,
+        EI: IntoIterator<Item = E>,
+        WI: IntoIterator<Item = PerFloat>,
+        E: BoolExpect,
+    {
+        let [mut correct, mut all] = [0, 0];
+        for ((input, expected), weight) in inputs.into_iter().zip(expecteds).zip(weights) {
+            if self
+                .train_on_weighted_sample(input, expected, weight)
+                .is_correct()
+            {
+                correct += 1;
+            }
+            all += 1;
+        }
+        Some(Ratio::new(correct, NonZeroU64::new(all)?.get()))
+    }
+
+    /// If you don't know the [`old_score`]:
+    /// + Pass `None` to [`old_score`] if you expect this algorithm to iterate exactly once.
+    /// + Pass zero to [`old_score`] otherwise.
+    fn fit<'i, II, EI, E>(
+        &mut self,
+        inputs: II,
+        expecteds: EI,
+        criteria: StoppingCriteria,
+        old_score: Option<Ratio<u64>>,
+    ) -> Option<Ratio<u64>>
+    where
+        X: 'i,
+        II: Copy + IntoIterator<Item = &'i X>,
+        EI: Copy + IntoIterator<Item = E>,
+        E: BoolExpect,
+    {
+        self.fit_recorded::<_, _, E>(inputs, expecteds, criteria, old_score)
+            .last_score()
+    }
+    /// Like [`Self::fit`], but returns records of all epochs, e.g. to plot a training curve.
+    fn fit_recorded<'i, II, EI, E>(
+        &mut self,
+        inputs: II,
+        expecteds: EI,
+        criteria: StoppingCriteria,
+        old_score: Option<Ratio<u64>>,
+    ) -> TrainingHistory
+    where
+        X: 'i,
+        II: Copy + IntoIterator<Item = &'i X>,
+        EI: Copy + IntoIterator<Item = E>,
+        E: BoolExpect,
+    {
+        let old_score = old_score.or_else(|| self.accuracy_for(inputs, expecteds));
+        let sample_count = inputs.into_iter().zip(expecteds).count() as u64;
+        let _span = tracing::info_span!("fit", units = 1, samples = sample_count).entered();
+        criteria.run_recorded(old_score, || self.train_epoch_on(inputs, expecteds))
+    }
+    /// Trains until more than `max_progress_reattemps` epochs in a row bring no accuracy progress.
+    fn fit_to<'i, II, EI, E>(
+        &mut self,
+        inputs: II,
+        expecteds: EI,
+        max_progress_reattemps: u64,
+    ) -> Option<Ratio<u64>>
+    where
+        X: 'i,
+        II: Copy + IntoIterator<Item = &'i X>,
+        EI: Copy + IntoIterator<Item = E>,
+        E: BoolExpect,
+    {
+        self.fit(
+            inputs,
+            expecteds,
+            StoppingCriteria::with_patience(max_progress_reattemps),
+            Some(zero()),
+        )
+    }
+}
+
+/// [`Neuron`] that decides by a hyperplane, i.e. `w·x >= theta`.
+pub trait LinearNeuron<const D: usize>: Neuron<PerVec<D>> {
+    // Required
+
+    fn wages(&self) -> &PerVec<D>;
+    fn theta(&self) -> PerFloat;
+
+    // Provided
+
+    /// Signed distance from the hyperplane scaled by `|w|`, i.e. `w·x - theta`.
+    fn net_input(&self, input: &PerVec<D>) -> PerFloat {
+        crate::util::sf32_vec::dot(self.wages(), input) - self.theta()
+    }
+}
+
+impl<const D: usize> Neuron<PerVec<D>> for crate::Perceptron<D> {
+    fn decide_for(&self, input: &PerVec<D>) -> bool {
+        self.decide_for(input)
+    }
+    fn train_on_weighted_sample<E: BoolExpect>(
+        &mut self,
+        input: &PerVec<D>,
+        expectation: E,
+        weight: PerFloat,
+    ) -> SampleOutcome {
+        self.train_on_weighted_sample(input, expectation, weight)
+    }
+    fn regularize(&mut self, regularization: &Regularization) {
+        self.regularize(regularization)
+    }
+    fn penalty(&self, regularization: &Regularization) -> PerFloat {
+        regularization.penalty(self.wages())
+    }
+}
+impl<const D: usize> LinearNeuron<D> for crate::Perceptron<D> {
+    fn wages(&self) -> &PerVec<D> {
+        self.wages()
+    }
+    fn theta(&self) -> PerFloat {
+        self.theta()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        util::{BoolExpectation, Correctness},
+        Adaline, OneLayerNN,
+    };
+
+    /// Unit implementing only the required methods. Decides `x >= theta` for scalar `x`.
+    #[derive(Default)]
+    struct Threshold {
+        theta: PerFloat,
+    }
+
+    impl Neuron<PerFloat> for Threshold {
+        fn decide_for(&self, input: &PerFloat) -> bool {
+            *input >= self.theta
+        }
+        fn train_on_weighted_sample<E: BoolExpect>(
+            &mut self,
+            input: &PerFloat,
+            expectation: E,
+            weight: PerFloat,
+        ) -> SampleOutcome {
+            if expectation.is_met_by(self.decide_for(input)) {
+                return SampleOutcome::new(Correctness::Correct, false);
+            }
+            self.theta += if self.decide_for(input) {
+                weight
+            } else {
+                -weight
+            };
+            SampleOutcome::new(Correctness::Incorrect, true)
+        }
+    }
+
+    #[test]
+    fn provided_methods_build_on_required_ones() {
+        let inputs = [1., 2., 3., 4.];
+        let expecteds = [false, false, false, true];
+        let mut unit = Threshold::default();
+        assert_eq!(
+            unit.accuracy_for(&inputs, expecteds),
+            Some(Ratio::new(1, 4))
+        );
+        assert_eq!(
+            unit.weighted_accuracy_for(&inputs, expecteds, [1., 1., 1., 5.]),
+            Some(5. / 8.)
+        );
+        assert_eq!(
+            unit.weighted_accuracy_for(&inputs, expecteds, [0.; 4]),
+            None
+        );
+
+        // Accuracy before learning from each sample.
+        assert_eq!(unit.train_on(&inputs, expecteds), Some(Ratio::new(1, 4)));
+        assert_eq!(unit.theta, 3.);
+        assert_eq!(unit.train_on(&[], expecteds), None);
+        let outcome = unit.train_epoch_on(&inputs, expecteds);
+        assert_eq!(
+            (outcome.score, outcome.updates),
+            (Some(Ratio::new(3, 4)), 1)
+        );
+
+        assert_eq!(
+            unit.fit_to(&inputs, expecteds, 2),
+            Some(Ratio::from_integer(1))
+        );
+        assert_eq!(unit.theta, 4.);
+    }
+
+    /// Asserts that training a default `U` on samples without expectation counts them correct and changes nothing.
+    fn assert_ignores_samples_without_expectation<X, U>(inputs: &[X])
+    where
+        U: Neuron<X> + Default + serde::Serialize,
+    {
+        let mut unit = U::default();
+        let untrained = serde_json::to_string(&unit).unwrap();
+        let expectations = inputs.iter().map(|_| BoolExpectation::NoExpect);
+        assert_eq!(
+            unit.train_on(inputs, expectations.clone()),
+            Some(Ratio::from_integer(1))
+        );
+        assert_eq!(
+            unit.accuracy_for(inputs, expectations),
+            Some(Ratio::from_integer(1))
+        );
+        assert_eq!(serde_json::to_string(&unit).unwrap(), untrained);
+    }
+
+    #[test]
+    fn samples_without_expectation_count_as_correct() {
+        let inputs = [PerVec::<2>::new(1., -1.), PerVec::<2>::new(-2., 3.)];
+        assert_ignores_samples_without_expectation::<_, crate::Perceptron<2>>(&inputs);
+        assert_ignores_samples_without_expectation::<_, Adaline<2>>(&inputs);
+        assert_ignores_samples_without_expectation::<_, crate::KernelPerceptron<2>>(&inputs);
+        let sparse_inputs = inputs.each_ref().map(crate::util::SparseVec::from);
+        assert_ignores_samples_without_expectation::<_, crate::SparsePerceptron>(&sparse_inputs);
+
+        let mut network = OneLayerNN::<2, 2>::default();
+        let expecteds = [
+            crate::PartialLabel::<u8>::Missing,
+            crate::PartialLabel::Missing,
+        ];
+        assert_eq!(
+            network.train_on(&inputs, expecteds.clone()),
+            Some(Ratio::from_integer(1))
+        );
+        assert!(network
+            .weights()
+            .iter()
+            .all(|(wages, theta)| wages.iter().all(|&w| w == 0.) && *theta == 0.));
+    }
+
+    #[test]
+    fn network_hosts_other_linear_units() {
+        let inputs = [-2., -1., 1., 2.].map(PerVec::<1>::new);
+        let expecteds = [0b01u8, 0b01, 0b10, 0b10];
+        let mut network = OneLayerNN::<2, 1, Adaline<1>>::default();
+        for _ in 0..50 {
+            network.train_on(&inputs, expecteds);
+        }
+        assert_eq!(
+            network.accuracy_for(&inputs, expecteds),
+            Some(Ratio::from_integer(1))
+        );
+        let [first, second] = network.weights();
+        assert!(first.0[0] < 0. && second.0[0] > 0.);
+    }
+}
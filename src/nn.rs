@@ -1,13 +1,15 @@
 pub mod one_layer {
-    use std::{num::NonZeroU64, ops::SubAssign};
+    use std::num::NonZeroU64;
 
     use crate::{
-        perceptron::PerVec,
-        util::{Correctness, IntExpect},
-        Perceptron,
+        history::TrainingHistory,
+        perceptron::{PerFloat, PerVec},
+        stopping::EpochOutcome,
+        util::{Correctness, IntExpect, SampleOutcome},
+        LinearNeuron, Neuron, Perceptron, Regularization, StoppingCriteria,
     };
     use num_rational::Ratio;
-    use num_traits::{zero, One, PrimInt, Zero};
+    use num_traits::{zero, PrimInt};
     use rayon::iter::{
         IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator,
         ParallelIterator,
@@ -16,18 +18,29 @@ pub mod one_layer {
     pub trait OutputInt: PrimInt + Send + Sync {}
     impl<T> OutputInt for T where T: PrimInt + Send + Sync {}
 
-    #[derive(Debug)]
-    pub struct OneLayerNN<const N: usize, const D: usize> {
-        perceptrons: [Perceptron<D>; N],
+    /// Network of `N` units taking `D`-dimensional input. Unit number `i` decides the `i`-th bit of the output.
+    ///
+    /// Units are [`Perceptron`]s by default, but any [`Neuron`] of `PerVec<D>` fits.
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+    #[serde(bound(
+        serialize = "U: serde::Serialize",
+        deserialize = "U: serde::Deserialize<'de>"
+    ))]
+    pub struct OneLayerNN<const N: usize, const D: usize, U = Perceptron<D>> {
+        #[serde(with = "crate::util::serde_array")]
+        neurons: [U; N],
+        /// Penalty applied to every unit after each training sample.
+        regularization: Regularization,
     }
 
     /// Implements `Default` for provided values of `N`.
     macro_rules! impl_default {
         ($N: expr) => {
-            impl<const D: usize> Default for OneLayerNN<$N, D> {
+            impl<const D: usize, U: Default> Default for OneLayerNN<$N, D, U> {
                 fn default() -> Self {
                     Self {
-                        perceptrons: Default::default(),
+                        neurons: Default::default(),
+                        regularization: Regularization::NONE,
                     }
                 }
             }
@@ -38,17 +51,75 @@ pub mod one_layer {
     }
     impl_default!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
 
-    impl<const N: usize, const D: usize> OneLayerNN<N, D> {
+    impl<const N: usize, const D: usize, U> OneLayerNN<N, D, U> {
+        // CRUD-C: Constructors
+
+        pub fn from_neurons(neurons: [U; N]) -> Self {
+            Self {
+                neurons,
+                regularization: Regularization::NONE,
+            }
+        }
+        /// Sets the penalty applied to every unit after each training sample, whether decided correctly or not.
+        pub fn with_regularization(mut self, regularization: Regularization) -> Self {
+            self.regularization = regularization;
+            self
+        }
+
+        // CRUD-R: Properties
+
+        pub fn neurons(&self) -> &[U; N] {
+            &self.neurons
+        }
+        pub fn regularization(&self) -> Regularization {
+            self.regularization
+        }
+
+        // CRUD-U: Setters
+
+        /// Allows editing units in place, e.g. their thresholds.
+        pub fn neurons_mut(&mut self) -> &mut [U; N] {
+            &mut self.neurons
+        }
+    }
+
+    impl<const N: usize, const D: usize, U> OneLayerNN<N, D, U>
+    where
+        U: LinearNeuron<D>,
+    {
+        // CRUD-R: Properties
+
+        /// Returns `(w, theta)` of each unit.
+        pub fn weights(&self) -> [(&PerVec<D>, PerFloat); N] {
+            std::array::from_fn(|idx| (self.neurons[idx].wages(), self.neurons[idx].theta()))
+        }
+        /// Returns `w·x - theta` of each unit. Unit's bit is set iff its value is non-negative.
+        pub fn net_inputs_for(&self, input: &PerVec<D>) -> [PerFloat; N] {
+            std::array::from_fn(|idx| self.neurons[idx].net_input(input))
+        }
+    }
+
+    impl<const N: usize, const D: usize, U> OneLayerNN<N, D, U>
+    where
+        U: Neuron<PerVec<D>> + Send + Sync,
+    {
         // CRUD-R: Properties
 
+        /// Current value of the regularization penalty summed over units.
+        pub fn penalty(&self) -> PerFloat {
+            self.neurons
+                .iter()
+                .map(|neuron| neuron.penalty(&self.regularization))
+                .sum()
+        }
         pub fn decide_for<I>(&self, input: &PerVec<D>) -> I
         where
             I: OutputInt,
         {
-            self.perceptrons
+            self.neurons
                 .par_iter()
                 .enumerate()
-                .map(|(idx, perceptron)| (idx, perceptron.decide_for(input)))
+                .map(|(idx, neuron)| (idx, neuron.decide_for(input)))
                 .fold(I::zero, |acc, (index, decision)| {
                     let bit = if decision { I::one() } else { I::zero() };
                     acc | (bit << index)
@@ -76,31 +147,84 @@ pub mod one_layer {
             }
             Some(Ratio::new(correct, NonZeroU64::new(all)?.get()))
         }
+        /// Returns the weighted share of correct decisions. `None` if weights sum to zero.
+        pub fn weighted_accuracy_for<'i, II, EI, WI, I, E>(
+            &self,
+            inputs: II,
+            expected: EI,
+            weights: WI,
+        ) -> Option<f64>
+        where
+            II: IntoIterator<Item = &'i PerVec<D>>,
+            EI: IntoIterator<Item = E>,
+            WI: IntoIterator<Item = PerFloat>,
+            I: OutputInt,
+            E: IntExpect<ProvidedInt = I>,
+        {
+            let [mut correct, mut all] = [0., 0.];
+            for ((input, expectation), weight) in inputs.into_iter().zip(expected).zip(weights) {
+                if expectation.is_met_by(self.decide_for::<I>(input)) {
+                    correct += f64::from(weight);
+                }
+                all += f64::from(weight);
+            }
+            (all != 0.).then(|| correct / all)
+        }
 
         // CRUD-U: Training [`self`].
 
+        /// Trains every unit on its bit of `expectation`.
+        /// Returns whether the whole output was correct and how many units updated their weights.
+        /// Regularization doesn't count as an update.
+        ///
         /// `E as PartialEq<I>` must be equivalence relation.
-        pub fn train_on_sample<I, E>(
+        pub fn train_on_sample<I, E>(&mut self, input: &PerVec<D>, expectation: E) -> SampleOutcome
+        where
+            I: OutputInt,
+            E: IntExpect + Sync + Send,
+        {
+            self.train_on_weighted_sample::<I, E>(input, expectation, 1.)
+        }
+        /// Like [`Self::train_on_sample`], but scales update steps by `weight`.
+        pub fn train_on_weighted_sample<I, E>(
             &mut self,
             input: &PerVec<D>,
             expectation: E,
-        ) -> crate::util::Correctness
+            weight: PerFloat,
+        ) -> SampleOutcome
         where
             I: OutputInt,
             E: IntExpect + Sync + Send,
         {
-            if self
-                .perceptrons
+            let regularization = self.regularization;
+            // Rayon threads don't inherit the caller's span.
+            let span = tracing::Span::current();
+            // Collected before judging, as a short-circuiting `all` would skip training some units.
+            let outcomes: Vec<SampleOutcome> = self
+                .neurons
                 .par_iter_mut()
                 .enumerate()
-                .map(move |(idx, perceptron)| {
-                    perceptron.train_on_sample(input, expectation.bit_expectation(idx))
+                .map(move |(idx, neuron)| {
+                    let _span = tracing::trace_span!(parent: &span, "unit", unit = idx).entered();
+                    let outcome = neuron.train_on_weighted_sample(
+                        input,
+                        expectation.bit_expectation(idx),
+                        weight,
+                    );
+                    if !regularization.is_none() {
+                        neuron.regularize(&regularization);
+                    }
+                    outcome
                 })
-                .all(|correctness| correctness.is_correct())
-            {
+                .collect();
+            let correctness = if outcomes.iter().all(SampleOutcome::is_correct) {
                 Correctness::Correct
             } else {
                 Correctness::Incorrect
+            };
+            SampleOutcome {
+                correctness,
+                updates: outcomes.iter().map(|outcome| outcome.updates).sum(),
             }
         }
 
@@ -116,9 +240,56 @@ pub mod one_layer {
             I: OutputInt,
             E: IntExpect<ProvidedInt = I> + Sync + Send,
         {
-            let [mut correct, mut all] = [0, 0];
+            self.train_epoch_on::<II, EI, I, E>(inputs, expecteds).score
+        }
+        /// Like [`Self::train_on`], but also reports weight updates made and the penalty reached.
+        pub fn train_epoch_on<'i, II, EI, I, E>(
+            &mut self,
+            inputs: II,
+            expecteds: EI,
+        ) -> EpochOutcome
+        where
+            II: IntoIterator<Item = &'i PerVec<D>>,
+            EI: IntoIterator<Item = E>,
+            I: OutputInt,
+            E: IntExpect<ProvidedInt = I> + Sync + Send,
+        {
+            let [mut correct, mut all, mut updates] = [0, 0, 0];
             for (input, expected) in inputs.into_iter().zip(expecteds) {
-                if self.train_on_sample::<I, E>(input, expected).is_correct() {
+                let outcome = self.train_on_sample::<I, E>(input, expected);
+                if outcome.is_correct() {
+                    correct += 1;
+                }
+                updates += outcome.updates;
+                all += 1;
+            }
+            EpochOutcome {
+                score: NonZeroU64::new(all).map(|all| Ratio::new(correct, all.get())),
+                sample_count: all,
+                updates,
+                penalty: self.penalty(),
+            }
+        }
+        /// Trains on every sample once, scaling update steps by sample weights.
+        pub fn train_on_weighted<'i, II, EI, WI, I, E>(
+            &mut self,
+            inputs: II,
+            expecteds: EI,
+            weights: WI,
+        ) -> Option<Ratio<u64>>
+        where
+            II: IntoIterator<Item = &'i PerVec<D>>,
+            EI: IntoIterator<Item = E>,
+            WI: IntoIterator<Item = PerFloat>,
+            I: OutputInt,
+            E: IntExpect<ProvidedInt = I> + Sync + Send,
+        {
+            let [mut correct, mut all] = [0, 0];
+            for ((input, expected), weight) in inputs.into_iter().zip(expecteds).zip(weights) {
+                if self
+                    .train_on_weighted_sample::<I, E>(input, expected, weight)
+                    .is_correct()
+                {
                     correct += 1;
                 }
                 all += 1;
@@ -129,51 +300,45 @@ pub mod one_layer {
         /// If you don't know the [`old_score`]:
         /// + Pass `None` to [`old_score`] if you expect this algorithm to iterate exactly once.
         /// + Pass zero to [`old_score`] otherwise.
-        pub fn fit<'i, II, EI, I, E, C>(
+        pub fn fit<'i, II, EI, I, E>(
             &mut self,
             inputs: II,
             expecteds: EI,
-            max_progress_reattemps: u64,
-            max_iterations: C,
-            mut old_score: Option<Ratio<u64>>,
+            criteria: StoppingCriteria,
+            old_score: Option<Ratio<u64>>,
         ) -> Option<Ratio<u64>>
         where
             II: Clone + IntoIterator<Item = &'i PerVec<D>>,
             EI: Clone + IntoIterator<Item = E>,
             I: OutputInt,
             E: IntExpect<ProvidedInt = I> + Sync + Send,
-            C: Zero + One + SubAssign,
         {
-            if old_score.is_none() {
-                old_score = self.accuracy_for(inputs.clone(), expecteds.clone())
-            }
-            let mut new_score = None;
-            let mut reattemps_left = max_progress_reattemps;
-
-            let mut iter_to_perform = max_iterations;
-            while !iter_to_perform.is_zero() {
-                new_score = self.train_on::<II, EI, I, E>(inputs.clone(), expecteds.clone());
-                use std::cmp::Ordering as O;
-                match new_score.cmp(&old_score) {
-                    O::Less | O::Equal => {
-                        // No-progress
-                        if reattemps_left == 0 {
-                            return new_score;
-                        }
-                        reattemps_left -= 1;
-                    }
-                    O::Greater => {
-                        // Progress ==> It's cool. ==> Let's continue.
-                        old_score = new_score;
-                        // Give algorithm more chances (reset them).
-                        reattemps_left = max_progress_reattemps;
-                    }
-                }
-                iter_to_perform -= C::one();
-            }
-            new_score
+            self.fit_recorded::<II, EI, I, E>(inputs, expecteds, criteria, old_score)
+                .last_score()
         }
-
+        /// Like [`Self::fit`], but returns records of all epochs, e.g. to plot a training curve.
+        pub fn fit_recorded<'i, II, EI, I, E>(
+            &mut self,
+            inputs: II,
+            expecteds: EI,
+            criteria: StoppingCriteria,
+            old_score: Option<Ratio<u64>>,
+        ) -> TrainingHistory
+        where
+            II: Clone + IntoIterator<Item = &'i PerVec<D>>,
+            EI: Clone + IntoIterator<Item = E>,
+            I: OutputInt,
+            E: IntExpect<ProvidedInt = I> + Sync + Send,
+        {
+            let old_score =
+                old_score.or_else(|| self.accuracy_for(inputs.clone(), expecteds.clone()));
+            let sample_count = inputs.clone().into_iter().zip(expecteds.clone()).count() as u64;
+            let _span = tracing::info_span!("fit", units = N, samples = sample_count).entered();
+            criteria.run_recorded(old_score, || {
+                self.train_epoch_on::<II, EI, I, E>(inputs.clone(), expecteds.clone())
+            })
+        }
+        /// Trains until more than `max_progress_reattemps` epochs in a row bring no accuracy progress.
         pub fn fit_to<'i, II, EI, I, E>(
             &mut self,
             inputs: II,
@@ -186,12 +351,82 @@ pub mod one_layer {
             I: OutputInt,
             E: IntExpect<ProvidedInt = I> + Sync + Send,
         {
-            return self.fit(
+            self.fit(
                 inputs,
                 expecteds,
-                max_progress_reattemps,
-                crate::util::OneAbsorbingSubtractions,
+                StoppingCriteria::with_patience(max_progress_reattemps),
                 Some(zero()),
+            )
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn training_on_sample_updates_every_wrong_unit() {
+            let mut network =
+                OneLayerNN::<16, 1>::default().with_regularization(Regularization::l2(0.5));
+            let input = PerVec::<1>::new(1.);
+
+            let outcome = network.train_on_sample::<u16, _>(&input, 0u16);
+
+            assert_eq!(outcome.correctness, Correctness::Incorrect);
+            assert_eq!(outcome.updates, 16);
+            for (wages, theta) in network.weights() {
+                // Rosenblatt step of 0.1 away from `true`, then L2 decay by `1 - 0.1 * 0.5`.
+                assert_eq!(wages[0], -0.1 * 0.95);
+                assert_eq!(theta, 0.1);
+            }
+        }
+
+        #[test]
+        fn fit_records_penalty_of_every_epoch() {
+            let mut network =
+                OneLayerNN::<1, 1>::default().with_regularization(Regularization::l2(0.5));
+            let inputs = [PerVec::<1>::new(1.)];
+
+            let history = network.fit_recorded::<_, _, u8, _>(
+                &inputs,
+                [0b0u8],
+                StoppingCriteria::with_max_epochs(3),
+                Some(zero()),
+            );
+
+            // One mistake, then weights only decay, also on correct decisions.
+            let penalties: Vec<_> = history
+                .records()
+                .iter()
+                .map(|record| record.penalty)
+                .collect();
+            assert_eq!(penalties.len(), 3);
+            assert!(penalties[0] > penalties[1] && penalties[1] > penalties[2]);
+            assert_eq!(penalties[2], network.penalty());
+            let wage = network.weights()[0].0[0];
+            assert!((network.penalty() - 0.25 * wage * wage).abs() < 1e-9);
+        }
+
+        #[test]
+        fn sample_weights_scale_update_steps() {
+            let mut network = OneLayerNN::<2, 1>::default();
+            let inputs = [PerVec::<1>::new(1.), PerVec::<1>::new(2.)];
+            let expecteds = [0b00u8, 0b00];
+
+            network.train_on_weighted(&inputs, expecteds, [0., 3.]);
+
+            // Both samples are missed, but only the second one steps, 3 times as far.
+            for (wages, theta) in network.weights() {
+                assert!((wages[0] - -0.6).abs() < 1e-6);
+                assert!((theta - 0.3).abs() < 1e-6);
+            }
+            assert_eq!(
+                network.weighted_accuracy_for(&inputs, [0b00u8, 0b11], [1., 3.]),
+                Some(0.25)
+            );
+            assert_eq!(
+                network.weighted_accuracy_for(&inputs, expecteds, [0., 0.]),
+                None
             );
         }
     }
@@ -0,0 +1,164 @@
+//! Learning from samples arriving one at a time.
+
+use num_rational::Ratio;
+
+use crate::{
+    nn::one_layer::OutputInt, perceptron::PerVec, util::IntExpect, Neuron, OneLayerNN, Perceptron,
+};
+
+/// Wraps [`OneLayerNN`] to learn from a stream of samples without storing them.
+///
+/// Every sample is first used to test the network and only then to train it (prequential evaluation).
+/// Thus accuracy is always measured on samples unseen at the time of decision.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(bound(
+    serialize = "U: serde::Serialize",
+    deserialize = "U: serde::Deserialize<'de>"
+))]
+pub struct OnlineLearner<const N: usize, const D: usize, U = Perceptron<D>> {
+    network: OneLayerNN<N, D, U>,
+    seen: u64,
+    correct: u64,
+    /// Weight kept by past samples whenever a new one arrives. `1` means no fading.
+    fading_factor: f64,
+    faded_seen: f64,
+    faded_correct: f64,
+}
+
+impl<const N: usize, const D: usize, U> Default for OnlineLearner<N, D, U>
+where
+    OneLayerNN<N, D, U>: Default,
+{
+    fn default() -> Self {
+        Self::new(OneLayerNN::default())
+    }
+}
+
+impl<const N: usize, const D: usize, U> OnlineLearner<N, D, U> {
+    // CRUD-C: Constructors
+
+    /// Continues learning of `network`, which can be untrained or pre-trained.
+    pub fn new(network: OneLayerNN<N, D, U>) -> Self {
+        Self {
+            network,
+            seen: 0,
+            correct: 0,
+            fading_factor: 1.,
+            faded_seen: 0.,
+            faded_correct: 0.,
+        }
+    }
+    /// Sets how much past samples count in [`Self::faded_accuracy`] whenever a new sample arrives.
+    ///
+    /// Values slightly below `1`, like `0.999`, make the faded accuracy follow recent performance.
+    ///
+    /// # Panics
+    /// * If `fading_factor` isn't in `(0, 1]`.
+    pub fn with_fading_factor(mut self, fading_factor: f64) -> Self {
+        assert!(
+            0. < fading_factor && fading_factor <= 1.,
+            "Fading factor should be in (0, 1], got {fading_factor}."
+        );
+        self.fading_factor = fading_factor;
+        self
+    }
+
+    // CRUD-R: Properties
+
+    pub fn network(&self) -> &OneLayerNN<N, D, U> {
+        &self.network
+    }
+    /// Number of samples learned from so far.
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+    /// Share of all samples decided correctly before learning from them.
+    pub fn prequential_accuracy(&self) -> Option<Ratio<u64>> {
+        (self.seen != 0).then(|| Ratio::new(self.correct, self.seen))
+    }
+    /// Like [`Self::prequential_accuracy`], but with older samples counting less, according to the fading factor.
+    pub fn faded_accuracy(&self) -> Option<f64> {
+        (self.faded_seen != 0.).then(|| self.faded_correct / self.faded_seen)
+    }
+
+    // CRUD-D: Consuming conversions
+
+    pub fn into_network(self) -> OneLayerNN<N, D, U> {
+        self.network
+    }
+}
+
+impl<const N: usize, const D: usize, U> OnlineLearner<N, D, U>
+where
+    U: Neuron<PerVec<D>> + Send + Sync,
+{
+    // CRUD-U: Learning
+
+    /// Tests the network on one sample and then trains it on that sample.
+    ///
+    /// Returns the decision made before training.
+    pub fn learn_one<I, E>(&mut self, input: &PerVec<D>, expectation: E) -> I
+    where
+        I: OutputInt,
+        E: IntExpect<ProvidedInt = I> + Sync + Send,
+    {
+        let decision = self.network.decide_for::<I>(input);
+        let is_correct = expectation.is_met_by(decision);
+        self.seen += 1;
+        self.faded_seen = self.fading_factor * self.faded_seen + 1.;
+        self.faded_correct *= self.fading_factor;
+        if is_correct {
+            self.correct += 1;
+            self.faded_correct += 1.;
+        }
+        self.network.train_on_sample::<I, E>(input, expectation);
+        decision
+    }
+    /// Learns from every sample of `stream` in turn. Returns the prequential accuracy afterwards.
+    pub fn learn_from<I, E, S>(&mut self, stream: S) -> Option<Ratio<u64>>
+    where
+        I: OutputInt,
+        E: IntExpect<ProvidedInt = I> + Sync + Send,
+        S: IntoIterator<Item = (PerVec<D>, E)>,
+    {
+        for (input, expectation) in stream {
+            self.learn_one(&input, expectation);
+        }
+        self.prequential_accuracy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decides_before_learning_from_each_sample() {
+        let mut learner = OnlineLearner::<2, 1>::default();
+        let input = PerVec::<1>::new(1.);
+        assert_eq!(learner.prequential_accuracy(), None);
+        // Untrained network decides `0b11` for non-negative inputs.
+        assert_eq!(learner.learn_one::<u8, _>(&input, 0b00u8), 0b11);
+        assert_eq!(learner.learn_one::<u8, _>(&input, 0b00u8), 0b00);
+        assert_eq!(learner.seen(), 2);
+        assert_eq!(learner.prequential_accuracy(), Some(Ratio::new(1, 2)));
+        assert_eq!(learner.faded_accuracy(), Some(0.5));
+    }
+
+    #[test]
+    fn fading_favours_recent_samples() {
+        let mut learner = OnlineLearner::<2, 1>::default().with_fading_factor(0.5);
+        let input = PerVec::<1>::new(1.);
+        let accuracy = learner.learn_from([(input, 0b00u8), (input, 0b00), (input, 0b00)]);
+        assert_eq!(accuracy, Some(Ratio::new(2, 3)));
+        // Mistake weighs 0.25, correct decisions 0.5 and 1.
+        assert_eq!(learner.faded_accuracy(), Some(1.5 / 1.75));
+        assert_eq!(learner.into_network().decide_for::<u8>(&input), 0b00);
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_fading_factor_panics() {
+        OnlineLearner::<2, 1>::default().with_fading_factor(0.);
+    }
+}
@@ -0,0 +1,289 @@
+//! Writing iris predictions in human- or machine-readable formats.
+
+use std::{fs::File, io::Write, path::Path};
+
+use anyhow::Context;
+use tabled::Tabled;
+
+use crate::perceptron::PerFloat;
+
+/// Format of printed predictions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Table for humans.
+    #[default]
+    Table,
+    /// CSV with a header row, delimited by the app's separator.
+    Csv,
+    /// One JSON array of objects.
+    Json,
+    /// One JSON object per line.
+    Jsonl,
+}
+
+/// Classification of one input iris.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Prediction {
+    /// Position of the iris among input irises, starting at 0.
+    pub index: usize,
+    pub iris: ic::UnclassifiedIris,
+    /// `None` if the classifier abstained.
+    pub classification: Option<ic::IrisSpecies>,
+    /// Per-species scores as given by [`crate::reject::class_scores`].
+    pub scores: Option<[PerFloat; 3]>,
+}
+
+/// What to write besides iris parameters and classification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OutputOptions {
+    pub format: OutputFormat,
+    /// Whether to write [`Prediction::index`].
+    pub with_index: bool,
+    /// Whether to write [`Prediction::scores`]. Predictions without them get empty values.
+    pub with_scores: bool,
+    /// Delimiter of CSV format.
+    pub separator: u8,
+}
+
+/// Text written in place of a withheld classification, except in JSON, where it is `null`.
+pub const UNCERTAIN: &str = "uncertain";
+
+const SPECIES: [ic::IrisSpecies; 3] = [
+    ic::IrisSpecies::Setosa,
+    ic::IrisSpecies::Versicolor,
+    ic::IrisSpecies::Virginica,
+];
+
+/// Creates file at `path`, or opens stdout if `path` is [`crate::read::STD_STREAM_PATH`].
+///
+/// Returned error reports what failed, not only why.
+pub fn create(path: impl AsRef<Path>) -> anyhow::Result<Box<dyn Write>> {
+    let path = path.as_ref();
+    Ok(if crate::read::is_std_stream(path) {
+        Box::new(std::io::stdout().lock())
+    } else {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create output file \"{}\".", path.display()))?;
+        Box::new(std::io::BufWriter::new(file))
+    })
+}
+
+/// Writes `predictions` in the chosen format.
+///
+/// Returned error reports what failed, not only why.
+pub fn write_predictions<W: Write>(
+    mut writer: W,
+    predictions: &[Prediction],
+    options: OutputOptions,
+) -> anyhow::Result<()> {
+    (|| -> anyhow::Result<()> {
+        match options.format {
+            OutputFormat::Table => {
+                let mut builder = tabled::builder::Builder::new();
+                builder.push_record(headers(options));
+                for prediction in predictions {
+                    builder.push_record(fields(prediction, options));
+                }
+                write!(writer, "{}", builder.build())?;
+            }
+            OutputFormat::Csv => {
+                let mut csv_writer = csv::WriterBuilder::new()
+                    .delimiter(options.separator)
+                    .from_writer(&mut writer);
+                csv_writer.write_record(headers(options))?;
+                for prediction in predictions {
+                    csv_writer.write_record(fields(prediction, options))?;
+                }
+                csv_writer.flush()?;
+            }
+            OutputFormat::Json => {
+                let objects: Vec<_> = predictions
+                    .iter()
+                    .map(|prediction| json_object(prediction, options))
+                    .collect();
+                serde_json::to_writer_pretty(&mut writer, &objects)?;
+                writeln!(writer)?;
+            }
+            OutputFormat::Jsonl => {
+                for prediction in predictions {
+                    serde_json::to_writer(&mut writer, &json_object(prediction, options))?;
+                    writeln!(writer)?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    })()
+    .context("Failed to write predictions.")
+}
+
+fn headers(options: OutputOptions) -> Vec<String> {
+    let mut headers = Vec::new();
+    if options.with_index {
+        headers.push("index".to_owned());
+    }
+    headers.extend(ic::ClassifiedIris::headers().into_iter().map(String::from));
+    if options.with_scores {
+        headers.extend(SPECIES.map(score_key));
+    }
+    headers
+}
+
+fn fields(prediction: &Prediction, options: OutputOptions) -> Vec<String> {
+    let mut fields = Vec::new();
+    if options.with_index {
+        fields.push(prediction.index.to_string());
+    }
+    fields.extend(
+        prediction
+            .iris
+            .as_na_svec()
+            .iter()
+            .map(|param| param.to_string()),
+    );
+    fields.push(
+        prediction
+            .classification
+            .map_or_else(|| UNCERTAIN.to_owned(), |species| species.to_string()),
+    );
+    if options.with_scores {
+        match prediction.scores {
+            Some(scores) => fields.extend(scores.map(|score| score.to_string())),
+            None => fields.extend([String::new(), String::new(), String::new()]),
+        }
+    }
+    fields
+}
+
+/// JSON shape of [`Prediction`], with fields in the order of other formats' columns.
+#[derive(serde::Serialize)]
+pub(crate) struct JsonPrediction {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<usize>,
+    sepal_length: PerFloat,
+    sepal_width: PerFloat,
+    petal_length: PerFloat,
+    petal_width: PerFloat,
+    classification: Option<String>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    scores: Option<JsonScores>,
+}
+
+#[derive(serde::Serialize)]
+struct JsonScores {
+    score_setosa: Option<PerFloat>,
+    score_versicolor: Option<PerFloat>,
+    score_virginica: Option<PerFloat>,
+}
+
+pub(crate) fn json_object(prediction: &Prediction, options: OutputOptions) -> JsonPrediction {
+    let [sepal_length, sepal_width, petal_length, petal_width] =
+        (*prediction.iris.as_na_svec()).into();
+    let score = |idx: usize| prediction.scores.map(|scores| scores[idx]);
+    JsonPrediction {
+        index: options.with_index.then_some(prediction.index),
+        sepal_length,
+        sepal_width,
+        petal_length,
+        petal_width,
+        classification: prediction.classification.map(|species| species.to_string()),
+        scores: options.with_scores.then(|| JsonScores {
+            score_setosa: score(0),
+            score_versicolor: score(1),
+            score_virginica: score(2),
+        }),
+    }
+}
+
+fn score_key(species: ic::IrisSpecies) -> String {
+    format!("score_{}", species.as_ref().to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn predictions() -> [Prediction; 2] {
+        [
+            Prediction {
+                index: 0,
+                iris: [5.1, 3.5, 1.4, 0.2].into(),
+                classification: Some(ic::IrisSpecies::Setosa),
+                scores: Some([1.5, -1.5, -0.5]),
+            },
+            Prediction {
+                index: 1,
+                iris: [6., 3., 4.5, 1.5].into(),
+                classification: None,
+                scores: None,
+            },
+        ]
+    }
+
+    fn written(options: OutputOptions) -> String {
+        let mut text = Vec::new();
+        write_predictions(&mut text, &predictions(), options).unwrap();
+        String::from_utf8(text).unwrap()
+    }
+
+    #[test]
+    fn csv_has_chosen_columns_and_separator() {
+        let options = OutputOptions {
+            format: OutputFormat::Csv,
+            separator: b';',
+            ..Default::default()
+        };
+        assert_eq!(
+            written(options),
+            "sepal_length;sepal_width;petal_length;petal_width;classification\n\
+             5.1;3.5;1.4;0.2;Setosa\n\
+             6;3;4.5;1.5;uncertain\n"
+        );
+        let options = OutputOptions {
+            with_index: true,
+            with_scores: true,
+            ..options
+        };
+        assert_eq!(
+            written(options),
+            "index;sepal_length;sepal_width;petal_length;petal_width;classification;score_setosa;score_versicolor;score_virginica\n\
+             0;5.1;3.5;1.4;0.2;Setosa;1.5;-1.5;-0.5\n\
+             1;6;3;4.5;1.5;uncertain;;;\n"
+        );
+    }
+
+    #[test]
+    fn json_formats_write_null_for_uncertain_classification() {
+        let options = OutputOptions {
+            format: OutputFormat::Jsonl,
+            with_scores: true,
+            ..Default::default()
+        };
+        let jsonl = written(options);
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["classification"], "Setosa");
+        assert_eq!(lines[0]["score_virginica"], -0.5);
+        assert!(lines[1]["classification"].is_null());
+        assert!(lines[1]["score_setosa"].is_null());
+        assert!(lines[1].get("index").is_none());
+
+        let json: serde_json::Value = serde_json::from_str(&written(OutputOptions {
+            format: OutputFormat::Json,
+            ..options
+        }))
+        .unwrap();
+        assert_eq!(json, serde_json::Value::Array(lines));
+    }
+
+    #[test]
+    fn table_marks_uncertain_classification() {
+        let table = written(OutputOptions::default());
+        assert!(table.contains("classification"));
+        assert!(table.contains("Setosa") && table.contains(UNCERTAIN));
+        assert!(!table.contains("score"));
+    }
+}
@@ -1,18 +1,25 @@
-use std::{num::NonZeroU64, ops::SubAssign};
-
 pub use nalgebra as na;
-use num_rational::Ratio;
-use num_traits::{one, zero, One, Zero};
 
-use crate::util::{BoolExpect, BoolExpectation, Correctness};
+use crate::util::{BoolExpect, BoolExpectation, Correctness, SampleOutcome};
 
 pub type PerFloat = f32;
 pub type PerVec<const D: usize> = na::SVector<PerFloat, D>;
 
-#[derive(Clone, Debug)]
+pub use update_rule::{PaVariant, UpdateRule};
+pub mod update_rule;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Perceptron<const D: usize> {
     wages: PerVec<D>,
     theta: PerFloat,
+    rule: UpdateRule,
+    /// Learning rate.
+    #[serde(default = "default_alpha")]
+    alpha: PerFloat,
+}
+
+fn default_alpha() -> PerFloat {
+    Perceptron::<0>::ALPHA
 }
 
 impl<const D: usize> Default for Perceptron<D> {
@@ -20,14 +27,42 @@ impl<const D: usize> Default for Perceptron<D> {
         Self {
             wages: na::SVector::zeros(),
             theta: 0.,
+            rule: UpdateRule::default(),
+            alpha: Self::ALPHA,
         }
     }
 }
 impl<const D: usize> Perceptron<D> {
+    /// Default learning rate.
     pub const ALPHA: PerFloat = 0.1;
 
+    // CRUD-C: Constructors
+
+    /// Sets the rule used in training.
+    pub fn with_rule(mut self, rule: UpdateRule) -> Self {
+        self.rule = rule;
+        self
+    }
+    /// Sets the learning rate, [`Self::ALPHA`] by default.
+    pub fn with_alpha(mut self, alpha: PerFloat) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
     // CRUD-R: Properties
 
+    pub fn rule(&self) -> UpdateRule {
+        self.rule
+    }
+    pub fn alpha(&self) -> PerFloat {
+        self.alpha
+    }
+    pub fn wages(&self) -> &PerVec<D> {
+        &self.wages
+    }
+    pub fn theta(&self) -> PerFloat {
+        self.theta
+    }
     /// Activation function.
     pub fn activation(&self, value: PerFloat) -> bool {
         value >= self.theta
@@ -37,138 +72,143 @@ impl<const D: usize> Perceptron<D> {
         let dot_prod = crate::util::sf32_vec::dot(&self.wages, input);
         self.activation(dot_prod)
     }
-    /// Returns accuracy this classifier has for the given test data.
-    pub fn accuracy_for<'i, II, EI, E>(&self, inputs: II, expectations: EI) -> Option<Ratio<u64>>
-    where
-        II: IntoIterator<Item = &'i PerVec<D>>,
-        EI: IntoIterator<Item = E>,
-        E: crate::util::BoolExpect,
-    {
-        let [mut correct, mut all] = [0, 0];
-        for (input, expectation) in inputs.into_iter().zip(expectations) {
-            if expectation.is_met_by(self.decide_for(input)) {
-                correct += 1;
-            }
-            all += 1;
-        }
-        Some(Ratio::new(correct, NonZeroU64::new(all)?.get()))
+
+    // CRUD-U: Setters
+
+    /// Sets the threshold `input` must reach to be decided `true`.
+    pub fn set_theta(&mut self, theta: PerFloat) {
+        self.theta = theta;
     }
 
     // CRUD-U: Training [`self`].
 
-    pub fn train_on_sample<E>(&mut self, input: &PerVec<D>, expectation: E) -> Correctness
+    /// Takes one step of the weight penalty.
+    pub fn regularize(&mut self, regularization: &crate::Regularization) {
+        regularization.apply(&mut self.wages, self.alpha);
+    }
+    /// Learns from one sample with update step scaled by `weight`. See [`crate::Neuron`] for training on many.
+    pub fn train_on_weighted_sample<E>(
+        &mut self,
+        input: &PerVec<D>,
+        expectation: E,
+        weight: PerFloat,
+    ) -> SampleOutcome
     where
         E: BoolExpect,
     {
         let prediction = self.decide_for(input);
         match expectation.expectation() {
             BoolExpectation::Expect(expectation) => {
-                if expectation.is_met_by(prediction) {
-                    return Correctness::Correct; // Correct, no need to improve
-                }
-                let translation_dir = match [expectation, prediction] {
-                    [false, true] => -1.,
-                    [true, false] => 1.,
-                    _ => {
-                        // Do nothing, nothing to improve
-                        return Correctness::Correct;
-                    }
+                let correctness = if expectation.is_met_by(prediction) {
+                    Correctness::Correct
+                } else {
+                    Correctness::Incorrect
+                };
+                let translation_dir = if expectation { 1. } else { -1. };
+                let Some(step) = self.step_size(input, translation_dir, correctness) else {
+                    return SampleOutcome::new(correctness, false); // No need to improve
                 };
-                let translation_multiplier = translation_dir * Self::ALPHA;
+                let translation_multiplier = translation_dir * step * weight;
+                if translation_multiplier == 0. {
+                    return SampleOutcome::new(correctness, false);
+                }
                 // Update self
                 crate::util::sf32_vec::add_assign(
                     &mut self.wages,
                     &(translation_multiplier * input),
                 );
                 self.theta -= translation_multiplier; // Input is -1.
-                Correctness::Incorrect // BUT improved
+                tracing::trace!(
+                    ?correctness,
+                    step = translation_multiplier,
+                    wages = ?self.wages.as_slice(),
+                    theta = self.theta,
+                    "Updated weights."
+                );
+                SampleOutcome::new(correctness, true) // BUT improved
             }
             BoolExpectation::NoExpect => {
                 // No expectation ==> nothing to do ==> everything is ok
-                Correctness::Correct
+                SampleOutcome::new(Correctness::Correct, false)
             }
         }
     }
-
-    pub fn train_on<'i, II, EI, E>(&mut self, inputs: II, expecteds: EI) -> Option<Ratio<u64>>
-    where
-        II: IntoIterator<Item = &'i PerVec<D>>,
-        EI: IntoIterator<Item = E>,
-        E: BoolExpect,
-    {
-        let [mut correct, mut all] = [0, 0];
-        for (input, expected) in inputs.into_iter().zip(expecteds) {
-            if self.train_on_sample(input, expected) == Correctness::Correct {
-                correct += 1;
+    /// Returns how far to step towards `translation_dir` according to [`UpdateRule`], or `None` if no update is needed.
+    fn step_size(
+        &self,
+        input: &PerVec<D>,
+        translation_dir: PerFloat,
+        correctness: Correctness,
+    ) -> Option<PerFloat> {
+        let signed_net =
+            || translation_dir * (crate::util::sf32_vec::dot(&self.wages, input) - self.theta);
+        match self.rule {
+            UpdateRule::Rosenblatt => (!correctness.is_correct()).then_some(self.alpha),
+            UpdateRule::Margin { margin } => {
+                (!correctness.is_correct() || signed_net() < margin).then_some(self.alpha)
+            }
+            UpdateRule::PassiveAggressive(variant) => {
+                let hinge_loss = (1. - signed_net()).max(0.);
+                // Theta's input of -1 counts into the squared norm.
+                (hinge_loss > 0.).then(|| variant.step_size(hinge_loss, input.norm_squared() + 1.))
             }
-            all += 1;
         }
-        Some(Ratio::new(correct, NonZeroU64::new(all)?.get()))
     }
+}
 
-    /// If you don't know the [`old_score`]:
-    /// + Pass `None` to [`old_score`] if you expect this algorithm to iterate exactly once.
-    /// + Pass zero to [`old_score`] otherwise.
-    pub fn fit<'i, II, EI, E, C>(
-        &mut self,
-        inputs: II,
-        expecteds: EI,
-        max_progress_reattemps: u64,
-        max_iterations: C,
-        mut old_score: Option<Ratio<u64>>,
-    ) -> Option<Ratio<u64>>
-    where
-        II: Copy + IntoIterator<Item = &'i PerVec<D>>,
-        EI: Copy + IntoIterator<Item = E>,
-        E: BoolExpect,
-        C: Zero + One + SubAssign,
-    {
-        if old_score.is_none() {
-            old_score = self.accuracy_for(inputs, expecteds)
-        }
-        let mut new_score = None;
-        let mut reattemps_left = max_progress_reattemps;
-
-        let mut iter_to_perform = max_iterations;
-        while !iter_to_perform.is_zero() {
-            new_score = self.train_on(inputs, expecteds);
-            use std::cmp::Ordering as O;
-            match new_score.cmp(&old_score) {
-                O::Less | O::Equal => {
-                    // No-progress
-                    if reattemps_left == 0 {
-                        return new_score;
-                    }
-                    reattemps_left -= 1;
-                }
-                O::Greater => {
-                    // Progress ==> It's cool. ==> Let's continue.
-                    old_score = new_score;
-                    // Give algorithm more chances (reset them).
-                    reattemps_left = max_progress_reattemps;
-                }
-            }
-            iter_to_perform -= one();
-        }
-        new_score
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rosenblatt_rule_steps_on_mistakes_only() {
+        let mut perceptron = Perceptron::<1>::default();
+        let input = PerVec::<1>::new(1.);
+        assert_eq!(
+            perceptron.train_on_weighted_sample(&input, true, 1.),
+            SampleOutcome::new(Correctness::Correct, false)
+        );
+        assert_eq!(perceptron.wages()[0], 0.);
+        assert_eq!(
+            perceptron.train_on_weighted_sample(&input, false, 2.),
+            SampleOutcome::new(Correctness::Incorrect, true)
+        );
+        assert_eq!((perceptron.wages()[0], perceptron.theta()), (-0.2, 0.2));
     }
-    pub fn fit_to<'i, II, EI, E, C>(
-        &mut self,
-        inputs: II,
-        expecteds: EI,
-        max_progress_reattemps: u64,
-    ) -> Option<Ratio<u64>>
-    where
-        II: Copy + IntoIterator<Item = &'i PerVec<D>>,
-        EI: Copy + IntoIterator<Item = E>,
-        E: BoolExpect,
-    {
-        return self.fit(
-            inputs,
-            expecteds,
-            max_progress_reattemps,
-            crate::util::OneAbsorbingSubtractions,
-            Some(zero()),
+
+    #[test]
+    fn margin_rule_steps_on_correct_decisions_near_the_boundary() {
+        let mut perceptron =
+            Perceptron::<1>::default().with_rule(UpdateRule::Margin { margin: 0.5 });
+        let input = PerVec::<1>::new(1.);
+        // Net input 0 is decided right, but within the margin.
+        assert_eq!(
+            perceptron.train_on_weighted_sample(&input, true, 1.),
+            SampleOutcome::new(Correctness::Correct, true)
         );
+        assert_eq!((perceptron.wages()[0], perceptron.theta()), (0.1, -0.1));
+        // Net input 0.2 is still within it.
+        perceptron.train_on_weighted_sample(&input, true, 1.);
+        // Net input 0.4 too, but then 0.6 is outside.
+        perceptron.train_on_weighted_sample(&input, true, 1.);
+        let wages = *perceptron.wages();
+        let outcome = perceptron.train_on_weighted_sample(&input, true, 1.);
+        assert_eq!(outcome.updates, 0);
+        assert_eq!(perceptron.wages(), &wages);
+    }
+
+    #[test]
+    fn passive_aggressive_rule_steps_just_far_enough() {
+        let mut perceptron =
+            Perceptron::<2>::default().with_rule(UpdateRule::PassiveAggressive(PaVariant::Pa));
+        let input = PerVec::<2>::new(1., 2.);
+        perceptron.train_on_weighted_sample(&input, false, 1.);
+        let net = crate::util::sf32_vec::dot(perceptron.wages(), &input) - perceptron.theta();
+        assert!((net + 1.).abs() < 1e-6, "{net}");
+        // Hinge loss is zero now.
+        let wages = *perceptron.wages();
+        let outcome = perceptron.train_on_weighted_sample(&input, false, 1.);
+        assert_eq!(outcome, SampleOutcome::new(Correctness::Correct, false));
+        assert_eq!(perceptron.wages(), &wages);
     }
 }
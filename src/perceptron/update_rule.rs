@@ -0,0 +1,52 @@
+//! Rules by which [`super::Perceptron`] updates its weights in training.
+
+use super::PerFloat;
+
+/// How [`super::Perceptron`] learns from a sample.
+///
+/// Below, `y` is `1` for expected `true` and `-1` for expected `false`, while `net` is `w·x - theta`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum UpdateRule {
+    /// Classic rule. Steps by the perceptron's `alpha` on misclassification only.
+    #[default]
+    Rosenblatt,
+    /// Steps by `alpha` also on correct decisions closer to the boundary than `margin`, i.e. when `y * net < margin`.
+    Margin { margin: PerFloat },
+    /// Steps whenever hinge loss `1 - y * net` is positive, as far as [`PaVariant`] decides.
+    PassiveAggressive(PaVariant),
+}
+
+/// Variant of the Passive-Aggressive step size.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum PaVariant {
+    /// Step just as far as needed to get `y * net >= 1`. Suits separable data.
+    Pa,
+    /// Step capped at aggressiveness `c`.
+    PaI { c: PerFloat },
+    /// Step softened by aggressiveness `c` through a `1/(2c)` term added to the squared norm.
+    PaII { c: PerFloat },
+}
+
+impl PaVariant {
+    /// Step size for hinge `loss` on input with squared norm `norm_squared`.
+    pub fn step_size(&self, loss: PerFloat, norm_squared: PerFloat) -> PerFloat {
+        match *self {
+            Self::Pa => loss / norm_squared,
+            Self::PaI { c } => c.min(loss / norm_squared),
+            Self::PaII { c } => loss / (norm_squared + 1. / (2. * c)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pa_variants_size_steps() {
+        assert_eq!(PaVariant::Pa.step_size(2., 4.), 0.5);
+        assert_eq!(PaVariant::PaI { c: 0.1 }.step_size(2., 4.), 0.1);
+        assert_eq!(PaVariant::PaI { c: 1. }.step_size(2., 4.), 0.5);
+        assert_eq!(PaVariant::PaII { c: 0.5 }.step_size(2., 4.), 0.4);
+    }
+}
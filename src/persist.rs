@@ -0,0 +1,36 @@
+//! Saving and loading of trained models as JSON files.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Saves `model` into a newly created file.
+///
+/// Returned error reports what failed, not only why.
+pub fn save<M: Serialize>(model: &M, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    (|| -> anyhow::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, model)?;
+        writer.flush()?;
+        Ok(())
+    })()
+    .with_context(|| format!("Failed to save model to \"{}\".", path.display()))
+}
+
+/// Loads model saved with [`save`].
+///
+/// Returned error reports what failed, not only why.
+pub fn load<M: DeserializeOwned>(path: impl AsRef<Path>) -> anyhow::Result<M> {
+    let path = path.as_ref();
+    (|| -> anyhow::Result<M> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    })()
+    .with_context(|| format!("Failed to load model from \"{}\".", path.display()))
+}
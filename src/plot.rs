@@ -0,0 +1,442 @@
+//! Pictures of what a [`OneLayerNN`] learned, in the plane of two chosen input dimensions.
+//!
+//! A plot shows predicted class regions, each perceptron's separating line (`w·x = theta`) and samples colored by class.
+//! Input dimensions other than the two plotted ones are fixed at their means over the samples.
+
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+
+use crate::{
+    nn::one_layer::OutputInt,
+    perceptron::{PerFloat, PerVec},
+    LinearNeuron, OneLayerNN,
+};
+
+/// Class colors as RGB. Classes beyond their count reuse them cyclically.
+const PALETTE: [[u8; 3]; 8] = [
+    [31, 119, 180],
+    [255, 127, 14],
+    [44, 160, 44],
+    [214, 39, 40],
+    [148, 103, 189],
+    [140, 86, 75],
+    [227, 119, 194],
+    [127, 127, 127],
+];
+const LINE_COLOR: [u8; 3] = [0, 0, 0];
+/// Side of a square of pixels sharing one region prediction.
+const REGION_CELL: u32 = 4;
+const POINT_RADIUS: f32 = 4.;
+
+/// Decision boundary plot, ready to be written as SVG or PNG.
+#[derive(Clone, Debug)]
+pub struct Plot {
+    width: u32,
+    height: u32,
+    /// `[min_x, max_x, min_y, max_y]` in input units.
+    bounds: [PerFloat; 4],
+    axis_names: [String; 2],
+    class_names: Vec<String>,
+    /// Predicted class of each region cell, row by row from the top.
+    regions: Vec<usize>,
+    /// Separating lines as pairs of ends in input units.
+    lines: Vec<[(PerFloat, PerFloat); 2]>,
+    points: Vec<(PerFloat, PerFloat, usize)>,
+}
+
+impl Plot {
+    // CRUD-C: Constructors
+
+    /// Plots `nn` in the plane of input dimensions `x_dim` and `y_dim` into a picture of `width`×`height` pixels.
+    ///
+    /// `samples` pair inputs with class indices. `decode` maps network outputs to class indices.
+    ///
+    /// # Errors
+    /// * If `x_dim` or `y_dim` is out of range or they are equal.
+    /// * If there are no samples.
+    pub fn new<const N: usize, const D: usize, U, I>(
+        nn: &OneLayerNN<N, D, U>,
+        samples: &[(PerVec<D>, usize)],
+        [x_dim, y_dim]: [usize; 2],
+        [width, height]: [u32; 2],
+        decode: impl Fn(I) -> usize,
+    ) -> anyhow::Result<Self>
+    where
+        U: LinearNeuron<D> + Send + Sync,
+        I: OutputInt,
+    {
+        if x_dim >= D || y_dim >= D || x_dim == y_dim {
+            bail!("Plotted dimensions should be 2 distinct ones out of 0..{D}, got {x_dim} and {y_dim}.");
+        }
+        if samples.is_empty() {
+            bail!("Nothing to plot, as there are no samples.");
+        }
+        let (width, height) = (width.max(1), height.max(1));
+        let fixed: PerVec<D> =
+            samples.iter().map(|(input, _)| input).sum::<PerVec<D>>() / samples.len() as PerFloat;
+        let bounds = {
+            let range = |dim: usize| {
+                let values = samples.iter().map(|(input, _)| input[dim]);
+                let min = values.clone().fold(PerFloat::INFINITY, PerFloat::min);
+                let max = values.fold(PerFloat::NEG_INFINITY, PerFloat::max);
+                let padding = ((max - min) * 0.05).max(0.5);
+                [min - padding, max + padding]
+            };
+            let ([min_x, max_x], [min_y, max_y]) = (range(x_dim), range(y_dim));
+            [min_x, max_x, min_y, max_y]
+        };
+        let mut plot = Self {
+            width,
+            height,
+            bounds,
+            axis_names: [format!("x{x_dim}"), format!("x{y_dim}")],
+            class_names: Vec::new(),
+            regions: Vec::new(),
+            lines: Vec::new(),
+            points: samples
+                .iter()
+                .map(|(input, class)| (input[x_dim], input[y_dim], *class))
+                .collect(),
+        };
+
+        let (columns, rows) = plot.region_grid();
+        for row in 0..rows {
+            for column in 0..columns {
+                let (x, y) = plot.to_input(
+                    ((column * REGION_CELL) as f32 + REGION_CELL as f32 / 2.).min(width as f32),
+                    ((row * REGION_CELL) as f32 + REGION_CELL as f32 / 2.).min(height as f32),
+                );
+                let mut input = fixed;
+                input[x_dim] = x;
+                input[y_dim] = y;
+                plot.regions.push(decode(nn.decide_for(&input)));
+            }
+        }
+        for (wages, theta) in nn.weights() {
+            // w_x·x + w_y·y = theta - (rest of w·x)
+            let rest: PerFloat = (0..D)
+                .filter(|&dim| dim != x_dim && dim != y_dim)
+                .map(|dim| wages[dim] * fixed[dim])
+                .sum();
+            if let Some(ends) = plot.clip_line(wages[x_dim], wages[y_dim], theta - rest) {
+                plot.lines.push(ends);
+            }
+        }
+        Ok(plot)
+    }
+    /// Names axes, which by default are named after the plotted dimensions, e.g. `x2`.
+    pub fn with_axis_names(mut self, x_name: impl Into<String>, y_name: impl Into<String>) -> Self {
+        self.axis_names = [x_name.into(), y_name.into()];
+        self
+    }
+    /// Names classes in the legend of SVG output. Class index `i` gets `class_names[i]`.
+    pub fn with_class_names(mut self, class_names: Vec<String>) -> Self {
+        self.class_names = class_names;
+        self
+    }
+
+    // CRUD-R: Rendering
+
+    /// Renders the plot as an SVG document.
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        let (width, height) = (self.width, self.height);
+        // Writing into `String` can't fail.
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        );
+        // Regions, with horizontal runs of the same class merged into one rectangle.
+        let (columns, _) = self.region_grid();
+        for (row, row_regions) in self.regions.chunks(columns as usize).enumerate() {
+            let mut run_start = 0;
+            for column in 1..=row_regions.len() {
+                if column == row_regions.len() || row_regions[column] != row_regions[run_start] {
+                    let _ = writeln!(
+                        svg,
+                        r#"<rect x="{}" y="{}" width="{}" height="{REGION_CELL}" fill="{}"/>"#,
+                        run_start as u32 * REGION_CELL,
+                        row as u32 * REGION_CELL,
+                        (column - run_start) as u32 * REGION_CELL,
+                        hex(region_color(row_regions[run_start])),
+                    );
+                    run_start = column;
+                }
+            }
+        }
+        for [start, end] in &self.lines {
+            let (x1, y1) = self.to_pixel(start.0, start.1);
+            let (x2, y2) = self.to_pixel(end.0, end.1);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{}" stroke-width="2"/>"#,
+                hex(LINE_COLOR)
+            );
+        }
+        for &(x, y, class) in &self.points {
+            let (cx, cy) = self.to_pixel(x, y);
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{cx}" cy="{cy}" r="{POINT_RADIUS}" fill="{}" stroke="black" stroke-width="0.5"/>"#,
+                hex(class_color(class))
+            );
+        }
+        let [x_name, y_name] = &self.axis_names;
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="end" font-family="sans-serif" font-size="12">{}</text>"#,
+            width.saturating_sub(4),
+            height.saturating_sub(4),
+            escape(x_name)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="4" y="14" font-family="sans-serif" font-size="12">{}</text>"#,
+            escape(y_name)
+        );
+        for (class, name) in self.class_names.iter().enumerate() {
+            let y = 30 + 16 * class;
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="10" cy="{}" r="{POINT_RADIUS}" fill="{}"/><text x="18" y="{y}" font-family="sans-serif" font-size="12">{}</text>"#,
+                y - 4,
+                hex(class_color(class)),
+                escape(name)
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+    /// Renders the plot as RGB pixels, row by row from the top. Texts (axis names, legend) are left out.
+    pub fn to_rgb(&self) -> Vec<u8> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (columns, _) = self.region_grid();
+        let mut pixels = vec![0; width * height * 3];
+        for (idx, pixel) in pixels.chunks_exact_mut(3).enumerate() {
+            let (px, py) = ((idx % width) as u32, (idx / width) as u32);
+            let cell = (py / REGION_CELL * columns + px / REGION_CELL) as usize;
+            pixel.copy_from_slice(&region_color(self.regions[cell]));
+        }
+        let mut put = |px: f32, py: f32, color: [u8; 3]| {
+            if (0. ..width as f32).contains(&px) && (0. ..height as f32).contains(&py) {
+                let idx = (py as usize * width + px as usize) * 3;
+                pixels[idx..idx + 3].copy_from_slice(&color);
+            }
+        };
+        for [start, end] in &self.lines {
+            let (x1, y1) = self.to_pixel(start.0, start.1);
+            let (x2, y2) = self.to_pixel(end.0, end.1);
+            let steps = ((x2 - x1).hypot(y2 - y1) * 2.).ceil().max(1.);
+            for step in 0..=steps as u32 {
+                let t = step as f32 / steps;
+                let (px, py) = (x1 + t * (x2 - x1), y1 + t * (y2 - y1));
+                for (dx, dy) in [(0., 0.), (1., 0.), (0., 1.), (1., 1.)] {
+                    put(px + dx - 0.5, py + dy - 0.5, LINE_COLOR);
+                }
+            }
+        }
+        for &(x, y, class) in &self.points {
+            let (cx, cy) = self.to_pixel(x, y);
+            let reach = POINT_RADIUS.ceil() as i32;
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let distance = (dx as f32).hypot(dy as f32);
+                    let color = if distance <= POINT_RADIUS - 1. {
+                        class_color(class)
+                    } else if distance <= POINT_RADIUS {
+                        [0, 0, 0]
+                    } else {
+                        continue;
+                    };
+                    put(cx + dx as f32, cy + dy as f32, color);
+                }
+            }
+        }
+        pixels
+    }
+
+    // CRUD-C: Writing files
+
+    /// Writes the plot as SVG or PNG, chosen by extension of `path`.
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("svg") => self.write_svg(path),
+            Some("png") => self.write_png(path),
+            _ => bail!(
+                "Can't tell image format of \"{}\". Use `.svg` or `.png` extension.",
+                path.display()
+            ),
+        }
+    }
+    /// Returned error reports what failed, not only why.
+    pub fn write_svg(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_svg())
+            .with_context(|| format!("Failed to write SVG plot to \"{}\".", path.display()))
+    }
+    /// Returned error reports what failed, not only why.
+    pub fn write_png(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        (|| -> anyhow::Result<()> {
+            let mut writer = BufWriter::new(File::create(path)?);
+            let mut encoder = png::Encoder::new(&mut writer, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.write_header()?.write_image_data(&self.to_rgb())?;
+            writer.flush()?;
+            Ok(())
+        })()
+        .with_context(|| format!("Failed to write PNG plot to \"{}\".", path.display()))
+    }
+
+    // Geometry
+
+    /// Numbers of region cell columns and rows.
+    fn region_grid(&self) -> (u32, u32) {
+        (
+            self.width.div_ceil(REGION_CELL),
+            self.height.div_ceil(REGION_CELL),
+        )
+    }
+    fn to_pixel(&self, x: PerFloat, y: PerFloat) -> (f32, f32) {
+        let [min_x, max_x, min_y, max_y] = self.bounds;
+        (
+            (x - min_x) / (max_x - min_x) * self.width as f32,
+            (max_y - y) / (max_y - min_y) * self.height as f32,
+        )
+    }
+    fn to_input(&self, px: f32, py: f32) -> (PerFloat, PerFloat) {
+        let [min_x, max_x, min_y, max_y] = self.bounds;
+        (
+            min_x + px / self.width as f32 * (max_x - min_x),
+            max_y - py / self.height as f32 * (max_y - min_y),
+        )
+    }
+    /// Clips line `a·x + b·y = c` to bounds. `None` if it misses them.
+    fn clip_line(
+        &self,
+        a: PerFloat,
+        b: PerFloat,
+        c: PerFloat,
+    ) -> Option<[(PerFloat, PerFloat); 2]> {
+        let [min_x, max_x, min_y, max_y] = self.bounds;
+        let mut ends = Vec::with_capacity(4);
+        if b != 0. {
+            for x in [min_x, max_x] {
+                let y = (c - a * x) / b;
+                if (min_y..=max_y).contains(&y) {
+                    ends.push((x, y));
+                }
+            }
+        }
+        if a != 0. {
+            for y in [min_y, max_y] {
+                let x = (c - b * y) / a;
+                if (min_x..=max_x).contains(&x) {
+                    ends.push((x, y));
+                }
+            }
+        }
+        ends.sort_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0).then(lhs.1.total_cmp(&rhs.1)));
+        Some([*ends.first()?, *ends.last()?])
+    }
+}
+
+fn class_color(class: usize) -> [u8; 3] {
+    PALETTE[class % PALETTE.len()]
+}
+/// Class color faded towards white.
+fn region_color(class: usize) -> [u8; 3] {
+    class_color(class).map(|channel| 255 - (255 - channel) / 3)
+}
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Perceptron;
+
+    /// Plot of a unit deciding `x >= 1`, between samples at `x = -1` and `x = 3`.
+    fn plot() -> Plot {
+        let mut perceptron = Perceptron::<2>::default();
+        // Deciding `true` for `-e0` is a mistake, so weights step to `0.1 * e0` and theta to `0.1`.
+        perceptron.train_on_weighted_sample(&PerVec::<2>::new(-1., 0.), false, 1.);
+        let nn = OneLayerNN::from_neurons([perceptron]);
+        let samples = [
+            (PerVec::<2>::new(-1., 0.), 0),
+            (PerVec::<2>::new(3., 0.), 1),
+        ];
+        Plot::new(&nn, &samples, [0, 1], [20, 8], |output: u8| output as usize).unwrap()
+    }
+
+    #[test]
+    fn rejects_bad_dimensions_and_missing_samples() {
+        let nn = OneLayerNN::<1, 2>::default();
+        let samples = [(PerVec::<2>::zeros(), 0)];
+        let decode = |output: u8| output as usize;
+        for dims in [[0, 0], [0, 2]] {
+            assert!(Plot::new(&nn, &samples, dims, [8, 8], decode).is_err());
+        }
+        assert!(Plot::new(&nn, &[], [0, 1], [8, 8], decode).is_err());
+    }
+
+    #[test]
+    fn svg_shows_regions_line_samples_and_legend() {
+        let svg = plot()
+            .with_axis_names("a<b", "y")
+            .with_class_names(vec!["low".into(), "high".into()])
+            .to_svg();
+        // Padded bounds are `-1.5..3.5`, so the line `x = 1` is in the middle.
+        assert!(
+            svg.contains(r#"<line x1="10" y1="8" x2="10" y2="0""#),
+            "{svg}"
+        );
+        assert!(svg.contains(&format!(r#"fill="{}""#, hex(region_color(0)))));
+        assert!(svg.contains(&format!(r#"fill="{}""#, hex(region_color(1)))));
+        // 2 samples and 2 legend entries.
+        assert_eq!(svg.matches("<circle").count(), 4);
+        assert!(svg.contains(">a&lt;b</text>") && svg.contains(">high</text>"));
+    }
+
+    #[test]
+    fn pixels_are_colored_by_region() {
+        let plot = plot();
+        let rgb = plot.to_rgb();
+        assert_eq!(rgb.len(), 20 * 8 * 3);
+        let pixel = |x: usize, y: usize| &rgb[(y * 20 + x) * 3..][..3];
+        assert_eq!(pixel(0, 0), region_color(0));
+        assert_eq!(pixel(19, 0), region_color(1));
+        assert_eq!(pixel(10, 4), LINE_COLOR);
+    }
+
+    #[test]
+    fn format_is_chosen_by_extension() -> anyhow::Result<()> {
+        let plot = plot();
+        assert!(plot.write("plot.gif").is_err());
+        let path = std::env::temp_dir().join(format!("perc_ic-plot-{}.png", std::process::id()));
+        plot.write(&path)?;
+        let png = std::fs::read(&path)?;
+        std::fs::remove_file(&path)?;
+        assert!(png.starts_with(b"\x89PNG"));
+        Ok(())
+    }
+}
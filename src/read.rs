@@ -0,0 +1,298 @@
+//! Dedicated to reading iris data.
+
+use std::{fs::File, io::Read, path::Path};
+
+use anyhow::{bail, Context};
+
+use crate::{
+    app_cfg,
+    data::{arff, PartialLabel},
+};
+
+// Helpful local type aliases.
+type UnclassifiedIrises = Vec<ic::UnclassifiedIris>;
+type ClassifiedIrises = Vec<ic::ClassifiedIris>;
+type PartiallyClassifiedIrises = Vec<(ic::UnclassifiedIris, PartialLabel<ic::IrisSpecies>)>;
+
+/// Path meaning stdin or stdout instead of a file.
+pub const STD_STREAM_PATH: &str = "-";
+
+/// Whether `path` is [`STD_STREAM_PATH`].
+pub fn is_std_stream(path: impl AsRef<Path>) -> bool {
+    path.as_ref() == Path::new(STD_STREAM_PATH)
+}
+
+/// Describes `path` for messages, e.g. `"data.csv"` or `stdin`.
+pub fn describe_source(path: impl AsRef<Path>) -> String {
+    let path = path.as_ref();
+    if is_std_stream(path) {
+        "stdin".to_owned()
+    } else {
+        format!("\"{}\"", path.display())
+    }
+}
+
+/// Opens file at `path`, or stdin if `path` is [`STD_STREAM_PATH`].
+fn open(path: &Path) -> std::io::Result<Box<dyn Read>> {
+    Ok(if is_std_stream(path) {
+        Box::new(std::io::stdin().lock())
+    } else {
+        Box::new(File::open(path)?)
+    })
+}
+
+fn log_read(path: &Path, iris_count: usize) {
+    tracing::info!(source = %path.display(), irises = iris_count, "Read irises.");
+}
+
+/// Reads users data from the input chosen by app arguments, stdin by default.
+///
+/// Returned error reports what failed, not only why.
+pub fn user_irises() -> anyhow::Result<UnclassifiedIrises> {
+    unclassified_irises(&app_cfg().input, app_cfg().training.data.separator.into())
+}
+
+/// Reads headerless CSV of unclassified irises with `separator` from file, or stdin if `path` is [`STD_STREAM_PATH`].
+///
+/// Returned error reports what failed, not only why.
+pub fn unclassified_irises(
+    path: impl AsRef<Path>,
+    separator: u8,
+) -> anyhow::Result<UnclassifiedIrises> {
+    let path = path.as_ref();
+    (|| -> anyhow::Result<_> {
+        let mut irises_reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(separator)
+            .from_reader(open(path)?);
+        let irises: Vec<_> = irises_reader.deserialize().collect::<Result<_, _>>()?;
+        log_read(path, irises.len());
+        Ok(irises)
+    })()
+    .with_context(|| {
+        format!(
+            "Failed to read unclassified irises from {}.",
+            describe_source(path)
+        )
+    })
+}
+
+/// Reads the training data from the file chosen by app arguments. Its labels can be partial.
+///
+/// Returned error reports what failed, not only why.
+pub fn training_irises() -> anyhow::Result<PartiallyClassifiedIrises> {
+    partially_classified_irises(&app_cfg().training.data.train)
+}
+
+/// Reads the testing data from the file chosen by app arguments.
+///
+/// Returned error reports what failed, not only why.
+pub fn testing_irises() -> anyhow::Result<ClassifiedIrises> {
+    classified_irises(&app_cfg().training.data.test)
+}
+
+/// Reads classified irises from file, or stdin if `path` is [`STD_STREAM_PATH`].
+///
+/// ARFF files (`.arff` extension) are recognized by extension. Other files and stdin are read as headerless CSV.
+///
+/// Returned error reports what failed, not only why.
+pub fn classified_irises(path: impl AsRef<Path>) -> anyhow::Result<ClassifiedIrises> {
+    let path = path.as_ref();
+    let is_arff = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("arff"));
+    if is_arff {
+        return arff_irises(path);
+    }
+    (|| -> anyhow::Result<_> {
+        let mut irises_reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(open(path)?);
+        let irises: Vec<_> = irises_reader.deserialize().collect::<Result<_, _>>()?;
+        log_read(path, irises.len());
+        Ok(irises)
+    })()
+    .with_context(|| {
+        format!(
+            "Failed to read classified irises from {}.",
+            describe_source(path)
+        )
+    })
+}
+
+/// Reads irises like [`classified_irises`], but accepts partial labels in CSV.
+///
+/// Label column of CSV can be `?` for a missing label, `|`-separated species for "one of these", e.g. `1|2` or `versicolor|virginica`,
+/// or `!` for none of the species. ARFF files accept classes `?` and `!` too.
+///
+/// Returned error reports what failed, not only why.
+pub fn partially_classified_irises(
+    path: impl AsRef<Path>,
+) -> anyhow::Result<PartiallyClassifiedIrises> {
+    let path = path.as_ref();
+    let is_arff = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("arff"));
+    if is_arff {
+        return arff_partial_irises(path).with_context(|| {
+            format!(
+                "Failed to read partially classified irises from \"{}\".",
+                path.display()
+            )
+        });
+    }
+    (|| -> anyhow::Result<_> {
+        let mut irises_reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(open(path)?);
+        let mut irises = Vec::new();
+        for (record_idx, record) in irises_reader.records().enumerate() {
+            let record_no = record_idx + 1;
+            let record = record?;
+            (|| -> anyhow::Result<()> {
+                let mut params = [0.; 4];
+                for (param_idx, param) in params.iter_mut().enumerate() {
+                    let field = record
+                        .get(param_idx)
+                        .with_context(|| format!("Missing column {}.", param_idx + 1))?;
+                    *param = field.trim().parse()?;
+                }
+                let label = record.get(4).context("Missing label column.")?;
+                irises.push((params.into(), species_label(label)?));
+                Ok(())
+            })()
+            .with_context(|| format!("Malformed record {record_no}."))?;
+        }
+        log_read(path, irises.len());
+        Ok(irises)
+    })()
+    .with_context(|| {
+        format!(
+            "Failed to read partially classified irises from {}.",
+            describe_source(path)
+        )
+    })
+}
+
+/// Reads classified irises from ARFF file, recognizing species by their nominal names.
+///
+/// Returned error reports what failed, not only why.
+pub fn arff_irises(path: impl AsRef<Path>) -> anyhow::Result<ClassifiedIrises> {
+    let path = path.as_ref();
+    (|| -> anyhow::Result<_> {
+        arff_partial_irises(path)?
+            .into_iter()
+            .enumerate()
+            .map(|(row_idx, (parameters, label))| match label {
+                PartialLabel::Known(species) => Ok(ic::ClassifiedIris::new(parameters, species)),
+                _ => bail!("Data row {} isn't labeled by a species.", row_idx + 1),
+            })
+            .collect()
+    })()
+    .with_context(|| {
+        format!(
+            "Failed to read classified irises from \"{}\".",
+            path.display()
+        )
+    })
+}
+
+/// Reads irises from ARFF file like [`arff_irises`], but accepts classes `?` for an unknown species and `!` for none of the species.
+///
+/// Returned error reports what failed, not only why.
+fn arff_partial_irises(path: &Path) -> anyhow::Result<PartiallyClassifiedIrises> {
+    let arff::ArffData {
+        classes, dataset, ..
+    } = arff::read_path::<4>(path)?;
+    let species = classes
+        .iter()
+        .map(|name| species_from_name(name))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let irises: PartiallyClassifiedIrises = dataset
+        .iter()
+        .map(|(input, label)| ((*input).into(), label.clone().map(|idx| species[idx])))
+        .collect();
+    log_read(path, irises.len());
+    Ok(irises)
+}
+
+/// Recognizes species by name, e.g. "Iris-setosa" or "virginica".
+pub fn species_from_name(name: &str) -> anyhow::Result<ic::IrisSpecies> {
+    use ic::IrisSpecies as S;
+    let lowercase = name.to_ascii_lowercase();
+    let name_core = lowercase.strip_prefix("iris-").unwrap_or(&lowercase);
+    Ok(match name_core {
+        "setosa" => S::Setosa,
+        "versicolor" => S::Versicolor,
+        "virginica" => S::Virginica,
+        _ => bail!("\"{name}\" isn't a recognized iris species."),
+    })
+}
+
+/// Recognizes a possibly partial label, where species are given by code or name.
+///
+/// See [`partially_classified_irises`] for the syntax.
+pub fn species_label(label: &str) -> anyhow::Result<PartialLabel<ic::IrisSpecies>> {
+    let species = |text: &str| -> anyhow::Result<ic::IrisSpecies> {
+        let text = text.trim();
+        match text.parse::<u8>() {
+            Ok(code) => ic::IrisSpecies::try_from(code)
+                .map_err(|_| anyhow::anyhow!("{code} isn't a valid iris species code.")),
+            Err(_) => species_from_name(text),
+        }
+    };
+    let label = label.trim();
+    Ok(if label == "?" {
+        PartialLabel::Missing
+    } else if label == "!" {
+        PartialLabel::Reject
+    } else if label.contains('|') {
+        PartialLabel::OneOf(
+            label
+                .split('|')
+                .map(species)
+                .collect::<anyhow::Result<_>>()?,
+        )
+    } else {
+        PartialLabel::Known(species(label)?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic::IrisSpecies as S;
+
+    #[test]
+    fn species_label_recognizes_codes_and_names() -> anyhow::Result<()> {
+        assert_eq!(species_label("0")?, PartialLabel::Known(S::Setosa));
+        assert_eq!(
+            species_label(" Iris-virginica ")?,
+            PartialLabel::Known(S::Virginica)
+        );
+        assert_eq!(
+            species_label("versicolor")?,
+            PartialLabel::Known(S::Versicolor)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn species_label_recognizes_partial_labels() -> anyhow::Result<()> {
+        assert_eq!(species_label("?")?, PartialLabel::Missing);
+        assert_eq!(species_label("!")?, PartialLabel::Reject);
+        assert_eq!(
+            species_label("1|virginica")?,
+            PartialLabel::OneOf(vec![S::Versicolor, S::Virginica])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn species_label_rejects_unknown_species() {
+        for label in ["3", "rose", "0|", ""] {
+            assert!(species_label(label).is_err(), "{label}");
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::perceptron::{PerFloat, PerVec};
+
+/// Weight penalty applied after every training sample.
+///
+/// Samples decided correctly decay weights too, so one epoch takes as many penalty steps as it has samples.
+/// Keeps weights from growing without bound on noisy data.
+/// Thresholds (theta) aren't penalized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Regularization {
+    /// Strength of L1 shrinkage (soft-thresholding). Drives small weights to exactly zero.
+    pub l1: PerFloat,
+    /// Strength of L2 weight decay. Shrinks weights proportionally to their size.
+    pub l2: PerFloat,
+}
+
+impl Regularization {
+    pub const NONE: Self = Self { l1: 0., l2: 0. };
+
+    // CRUD-C: Constructors
+
+    pub fn l1(l1: PerFloat) -> Self {
+        Self { l1, l2: 0. }
+    }
+    pub fn l2(l2: PerFloat) -> Self {
+        Self { l1: 0., l2 }
+    }
+
+    // CRUD-R: Properties
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+    /// Value of the penalty term, i.e. `l1 * |w|₁ + l2 / 2 * |w|²`.
+    pub fn penalty<const D: usize>(&self, wages: &PerVec<D>) -> PerFloat {
+        self.l1 * wages.lp_norm(1) + self.l2 / 2. * wages.norm_squared()
+    }
+
+    // CRUD-U: Applying to weights
+
+    /// Takes one gradient step of size `alpha` on the penalty term.
+    pub fn apply<const D: usize>(&self, wages: &mut PerVec<D>, alpha: PerFloat) {
+        if self.l2 != 0. {
+            *wages *= 1. - alpha * self.l2;
+        }
+        if self.l1 != 0. {
+            let threshold = alpha * self.l1;
+            wages.apply(|w| *w = w.signum() * (w.abs() - threshold).max(0.));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn l2_decays_weights_proportionally() {
+        let mut wages = PerVec::<2>::new(2., -4.);
+        Regularization::l2(0.5).apply(&mut wages, 0.1);
+        assert_eq!(wages, PerVec::<2>::new(1.9, -3.8));
+    }
+
+    #[test]
+    fn l1_shrinks_small_weights_to_exactly_zero() {
+        let mut wages = PerVec::<3>::new(0.5, -0.05, -2.);
+        Regularization::l1(1.).apply(&mut wages, 0.1);
+        assert_eq!(wages, PerVec::<3>::new(0.4, 0., -1.9));
+    }
+
+    #[test]
+    fn none_leaves_weights_alone() {
+        let mut wages = PerVec::<2>::new(2., -4.);
+        assert!(Regularization::NONE.is_none());
+        assert!(Regularization::default().is_none());
+        Regularization::NONE.apply(&mut wages, 0.1);
+        assert_eq!(wages, PerVec::<2>::new(2., -4.));
+        assert_eq!(Regularization::NONE.penalty(&wages), 0.);
+    }
+
+    #[test]
+    fn penalty_sums_both_terms() {
+        let wages = PerVec::<2>::new(3., -4.);
+        let regularization = Regularization { l1: 0.1, l2: 0.2 };
+        assert!(!regularization.is_none());
+        // 0.1 * 7 + 0.2 / 2 * 25
+        assert!((regularization.penalty(&wages) - 3.2).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,192 @@
+//! Abstaining from classification when the iris network isn't confident.
+//!
+//! Confidence of a decision is the smallest distance of the iris from hyperplanes of the network's perceptrons,
+//! as every output bit tells species apart. The reject output is rejected by any threshold, see [`crate::prediction_to_judgement`].
+
+use num_rational::Ratio;
+use tabled::Tabled;
+
+use crate::{perceptron::PerFloat, prediction_to_judgement, IrisNetwork};
+
+/// Signed distances of `iris` from hyperplanes of the network's perceptrons.
+///
+/// Positive distance means the perceptron outputs `true`.
+pub fn margins(nn: &IrisNetwork, iris: &ic::UnclassifiedIris) -> [PerFloat; 2] {
+    let net_inputs = nn.net_inputs_for(iris.as_na_svec());
+    let weights = nn.weights();
+    std::array::from_fn(|idx| {
+        let norm = weights[idx].0.norm();
+        if norm == 0. {
+            // Untrained perceptron ==> Its decision is no better than a guess.
+            0.
+        } else {
+            net_inputs[idx] / norm
+        }
+    })
+}
+
+/// Confidence of the network's decision for `iris`. See [module docs](self).
+pub fn confidence(nn: &IrisNetwork, iris: &ic::UnclassifiedIris) -> PerFloat {
+    let [virginica_margin, setosa_margin] = margins(nn, iris);
+    setosa_margin.abs().min(virginica_margin.abs())
+}
+
+/// Score of each species, indexed by species code. Only the decided species scores non-negative, none for the reject output.
+///
+/// Score of the decided species equals [`confidence`], other scores tell how far the iris is from being decided so.
+pub fn class_scores(nn: &IrisNetwork, iris: &ic::UnclassifiedIris) -> [PerFloat; 3] {
+    let [virginica_margin, setosa_margin] = margins(nn, iris);
+    [
+        setosa_margin.min(-virginica_margin),
+        (-setosa_margin).min(-virginica_margin),
+        (-setosa_margin).min(virginica_margin),
+    ]
+}
+
+/// Classifies `iris`, unless the network decides the reject output.
+pub fn classify(nn: &IrisNetwork, iris: &ic::UnclassifiedIris) -> Option<ic::IrisSpecies> {
+    prediction_to_judgement(nn.decide_for(iris.as_na_svec()))
+}
+
+/// Like [`classify`], but rejects also irises with confidence below `threshold`.
+pub fn classify_or_reject(
+    nn: &IrisNetwork,
+    iris: &ic::UnclassifiedIris,
+    threshold: PerFloat,
+) -> Option<ic::IrisSpecies> {
+    (confidence(nn, iris) >= threshold)
+        .then(|| classify(nn, iris))
+        .flatten()
+}
+
+/// Outcome of classifying with one reject threshold.
+#[derive(Clone, Copy, Debug, PartialEq, Tabled)]
+pub struct CoveragePoint {
+    pub threshold: PerFloat,
+    /// Share of irises not rejected.
+    pub coverage: Ratio<u64>,
+    /// Accuracy on irises not rejected.
+    pub accuracy: Ratio<u64>,
+}
+
+/// Returns coverage and accuracy for every threshold that rejects a different set of `irises`.
+///
+/// Points are ordered from full coverage to the lowest one.
+pub fn coverage_curve(nn: &IrisNetwork, irises: &[ic::ClassifiedIris]) -> Vec<CoveragePoint> {
+    let mut judged: Vec<(PerFloat, bool)> = irises
+        .iter()
+        .map(|iris| {
+            (
+                confidence(nn, &iris.parameters),
+                classify(nn, &iris.parameters) == Some(iris.classification),
+            )
+        })
+        .collect();
+    judged.sort_by(|(lhs, _), (rhs, _)| rhs.total_cmp(lhs));
+
+    let all = judged.len() as u64;
+    let mut points = Vec::new();
+    let [mut covered, mut correct] = [0, 0];
+    for (idx, &(confidence, is_correct)) in judged.iter().enumerate() {
+        covered += 1;
+        correct += u64::from(is_correct);
+        // Ties are all covered or all rejected by any threshold.
+        let is_last_of_tie = judged.get(idx + 1).map(|&(next, _)| next) != Some(confidence);
+        if is_last_of_tie {
+            points.push(CoveragePoint {
+                threshold: confidence,
+                coverage: Ratio::new(covered, all),
+                accuracy: Ratio::new(correct, covered),
+            });
+        }
+    }
+    points.reverse();
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use ic::IrisSpecies as S;
+
+    use super::*;
+    use crate::{perceptron::PerVec, OneLayerNN, Perceptron};
+
+    /// Network whose Setosa margin is `sepal_length - 1` and Virginica margin `sepal_width - 1`.
+    fn network() -> IrisNetwork {
+        let unit = |param_idx| {
+            let mut perceptron = Perceptron::<4>::default();
+            // Deciding `true` for `-e` is a mistake, so weights step to `0.1 * e` and theta to `0.1`.
+            perceptron.train_on_weighted_sample(&-PerVec::<4>::ith(param_idx, 1.), false, 1.);
+            perceptron
+        };
+        OneLayerNN::from_neurons([unit(1), unit(0)])
+    }
+
+    fn iris(sepal_length: PerFloat, sepal_width: PerFloat) -> ic::UnclassifiedIris {
+        [sepal_length, sepal_width, 0., 0.].into()
+    }
+
+    fn assert_close<const N: usize>(actual: [PerFloat; N], expected: [PerFloat; N]) {
+        for (actual, expected) in actual.into_iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-5, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn confidence_is_the_smallest_margin() {
+        let nn = network();
+        assert_close(margins(&nn, &iris(3., 0.5)), [-0.5, 2.]);
+        assert_close([confidence(&nn, &iris(3., 0.5))], [0.5]);
+        assert_close([confidence(&nn, &iris(0.5, 1.25))], [0.25]);
+        assert_close(class_scores(&nn, &iris(3., 0.5)), [0.5, -2., -2.]);
+        assert_close(class_scores(&nn, &iris(0.5, 1.25)), [-0.5, -0.25, 0.25]);
+        // Both perceptrons firing is the reject output.
+        assert_close(class_scores(&nn, &iris(3., 5.)), [-4., -4., -2.]);
+    }
+
+    #[test]
+    fn untrained_network_has_no_confidence() {
+        let nn = IrisNetwork::default();
+        assert_eq!(confidence(&nn, &iris(3., 5.)), 0.);
+        assert_eq!(classify_or_reject(&nn, &iris(3., 5.), 0.1), None);
+        assert_eq!(classify(&nn, &iris(3., 5.)), None);
+    }
+
+    #[test]
+    fn rejects_below_threshold() {
+        let nn = network();
+        assert_eq!(
+            classify_or_reject(&nn, &iris(3., 0.5), 0.5),
+            Some(S::Setosa)
+        );
+        assert_eq!(classify_or_reject(&nn, &iris(3., 0.5), 1.), None);
+        assert_eq!(classify_or_reject(&nn, &iris(0.5, 1.25), 1.), None);
+        assert_eq!(classify_or_reject(&nn, &iris(3., 5.), 0.), None);
+        assert_eq!(
+            classify_or_reject(&nn, &iris(0.5, 1.25), 0.),
+            Some(S::Virginica)
+        );
+    }
+
+    #[test]
+    fn coverage_curve_merges_tied_confidences() {
+        let nn = network();
+        let irises = [
+            (iris(3., 0.), S::Setosa),
+            (iris(0.5, 1.5), S::Versicolor),
+            (iris(0.5, 0.5), S::Versicolor),
+        ]
+        .map(|(parameters, species)| ic::ClassifiedIris::new(parameters, species));
+        let curve = coverage_curve(&nn, &irises);
+        assert_eq!(curve.len(), 2);
+        assert_eq!(
+            (curve[0].coverage, curve[0].accuracy),
+            (Ratio::from_integer(1), Ratio::new(2, 3))
+        );
+        assert_eq!(
+            (curve[1].coverage, curve[1].accuracy),
+            (Ratio::new(1, 3), Ratio::from_integer(1))
+        );
+        assert!(curve[0].threshold < curve[1].threshold);
+    }
+}
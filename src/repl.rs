@@ -0,0 +1,342 @@
+//! Interactive exploration of a trained [`IrisNetwork`].
+//!
+//! Every line is a command. A line of 4 numbers, separated by spaces or commas, is classified,
+//! showing net value and bit of each perceptron, the output of [`OneLayerNN::decide_for`] and its species.
+//! See [`HELP`] for other commands.
+//!
+//! [`OneLayerNN::decide_for`]: crate::OneLayerNN::decide_for
+
+use std::{
+    io::{BufRead, Write},
+    ops::ControlFlow,
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+
+use crate::{
+    config::{TrainedModel, TrainingConfig},
+    perceptron::PerFloat,
+    prediction_to_judgement, read, reject, ClassificationExpectation, IrisNetwork, PartialLabel,
+};
+
+type PartiallyClassifiedIris = (ic::UnclassifiedIris, PartialLabel<ic::IrisSpecies>);
+
+/// Commands understood by [`Repl`].
+pub const HELP: &str = "\
+<sl> <sw> <pl> <pw>          classify an iris given by its 4 parameters
+weights                      show weights and threshold of each perceptron
+theta <unit> <value>         set threshold of a perceptron
+reject <value> | reject off  set or unset minimal confidence needed to classify
+learn <sl> <sw> <pl> <pw> <label>
+                             train on a corrected iris until it's classified as labeled
+retrain                      train a new network on training irises and learned ones
+save <path>                  save the network, e.g. for `serve`
+help                         show this help
+quit | exit                  leave";
+
+/// Most training passes [`Repl`] makes over one corrected iris.
+pub const MAX_LEARNING_PASSES: usize = 1000;
+
+/// State of an interactive session. See [module docs](self).
+#[derive(Clone, Debug)]
+pub struct Repl {
+    network: IrisNetwork,
+    training_irises: Vec<PartiallyClassifiedIris>,
+    /// Irises given by `learn` commands.
+    learned_irises: Vec<PartiallyClassifiedIris>,
+    /// Config used by `retrain` and saved with the network.
+    config: TrainingConfig,
+    reject_threshold: Option<PerFloat>,
+}
+
+impl Repl {
+    // CRUD-C: Constructors
+
+    /// `training_irises` are used, along with learned ones, by `retrain`.
+    pub fn new(network: IrisNetwork, training_irises: Vec<PartiallyClassifiedIris>) -> Self {
+        Self {
+            network,
+            training_irises,
+            learned_irises: Vec::new(),
+            config: TrainingConfig::default(),
+            reject_threshold: None,
+        }
+    }
+    /// Sets the config used by `retrain` and saved with the network, the default one otherwise.
+    pub fn with_config(mut self, config: TrainingConfig) -> Self {
+        self.config = config;
+        self
+    }
+    /// Makes irises less confident than `threshold` be classified as uncertain.
+    pub fn with_reject_threshold(mut self, threshold: Option<PerFloat>) -> Self {
+        self.reject_threshold = threshold;
+        self
+    }
+
+    // CRUD-R: Properties
+
+    pub fn network(&self) -> &IrisNetwork {
+        &self.network
+    }
+    pub fn learned_irises(&self) -> &[PartiallyClassifiedIris] {
+        &self.learned_irises
+    }
+
+    // CRUD-U: Running
+
+    /// Executes commands read line by line until `quit` or end of `reader`.
+    ///
+    /// Failed commands are reported to `writer` and don't end the session. `prompt` is written before each command.
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn run<R: BufRead, W: Write>(
+        &mut self,
+        reader: R,
+        mut writer: W,
+        prompt: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let mut lines = reader.lines();
+        loop {
+            if let Some(prompt) = prompt {
+                write!(writer, "{prompt}")?;
+                writer.flush()?;
+            }
+            let Some(line) = lines.next() else {
+                break;
+            };
+            let line = line.context("Failed to read command.")?;
+            match self.execute(&line, &mut writer) {
+                Ok(ControlFlow::Continue(())) => {}
+                Ok(ControlFlow::Break(())) => break,
+                Err(err) => writeln!(writer, "Error: {err:#}")?,
+            }
+        }
+        Ok(())
+    }
+    /// Executes one command, writing its outcome to `writer`. Breaks on `quit`.
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn execute<W: Write>(
+        &mut self,
+        line: &str,
+        mut writer: W,
+    ) -> anyhow::Result<ControlFlow<()>> {
+        let words: Vec<&str> = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty())
+            .collect();
+        match words.as_slice() {
+            [] => {}
+            ["quit" | "exit"] => return Ok(ControlFlow::Break(())),
+            ["help"] => writeln!(writer, "{HELP}")?,
+            ["weights"] => self.write_weights(&mut writer)?,
+            ["theta", unit, value] => {
+                let unit: usize = parse(unit, "perceptron number")?;
+                let units = self.network.neurons_mut();
+                let unit_count = units.len();
+                let Some(perceptron) = units.get_mut(unit) else {
+                    bail!("There are only perceptrons 0 to {}.", unit_count - 1);
+                };
+                perceptron.set_theta(parse(value, "threshold")?);
+            }
+            ["reject", "off"] => self.reject_threshold = None,
+            ["reject", value] => self.reject_threshold = Some(parse(value, "reject threshold")?),
+            ["learn", params @ .., label] => {
+                let iris = parse_iris(params)?;
+                let label = read::species_label(label)?;
+                let updates = self.learn(iris, label.clone())?;
+                writeln!(writer, "Learned after {updates} weight updates.")?;
+                self.learned_irises.push((iris, label));
+            }
+            ["retrain"] => {
+                let irises = [&self.training_irises[..], &self.learned_irises[..]].concat();
+                let count = irises.len();
+                self.network = crate::train_iris_network_with(irises, &self.config)?;
+                writeln!(writer, "Retrained on {count} irises.")?;
+            }
+            ["save", path] => {
+                let model = TrainedModel {
+                    network: self.network.clone(),
+                    config: self.config.clone(),
+                };
+                model.save(Path::new(path))?;
+                writeln!(writer, "Saved to \"{path}\".")?;
+            }
+            params
+                if params
+                    .first()
+                    .is_some_and(|word| word.parse::<f32>().is_ok()) =>
+            {
+                self.write_decision(parse_iris(params)?, &mut writer)?;
+            }
+            [command, ..] => bail!("Unknown command \"{command}\". Type `help` for commands."),
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    fn write_weights<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        for (unit, (weights, theta)) in self.network.weights().into_iter().enumerate() {
+            let weights: Vec<String> = weights.iter().map(|weight| weight.to_string()).collect();
+            writeln!(
+                writer,
+                "unit {unit}: w = [{}], theta = {theta}",
+                weights.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_decision<W: Write>(
+        &self,
+        iris: ic::UnclassifiedIris,
+        mut writer: W,
+    ) -> std::io::Result<()> {
+        let net_values = self.network.net_inputs_for(iris.as_na_svec());
+        for (unit, net_value) in net_values.into_iter().enumerate() {
+            writeln!(
+                writer,
+                "unit {unit}: net = {net_value} -> bit {}",
+                u8::from(net_value >= 0.)
+            )?;
+        }
+        let output: u8 = self.network.decide_for(iris.as_na_svec());
+        let confidence = reject::confidence(&self.network, &iris);
+        let species = match (self.reject_threshold, prediction_to_judgement(output)) {
+            (Some(threshold), _) if confidence < threshold => crate::output::UNCERTAIN.to_owned(),
+            (_, Some(species)) => species.to_string(),
+            (_, None) => crate::output::UNCERTAIN.to_owned(),
+        };
+        writeln!(
+            writer,
+            "output: {output:#04b} = {output} -> {species} (confidence {confidence})"
+        )
+    }
+
+    /// Trains on `iris` until it meets `label`. Returns the number of weight updates made, counted per unit.
+    fn learn(
+        &mut self,
+        iris: ic::UnclassifiedIris,
+        label: PartialLabel<ic::IrisSpecies>,
+    ) -> anyhow::Result<u64> {
+        let expectation = label.map(ClassificationExpectation::from);
+        let mut updates = 0;
+        for _ in 0..MAX_LEARNING_PASSES {
+            let outcome = self
+                .network
+                .train_on_sample::<u8, _>(iris.as_na_svec(), expectation.clone());
+            updates += outcome.updates;
+            if outcome.is_correct() {
+                return Ok(updates);
+            }
+        }
+        bail!("Failed to learn the iris within {MAX_LEARNING_PASSES} training passes.")
+    }
+}
+
+fn parse<T: std::str::FromStr>(word: &str, what: &str) -> anyhow::Result<T> {
+    word.parse()
+        .map_err(|_| anyhow::anyhow!("\"{word}\" isn't a valid {what}."))
+}
+
+fn parse_iris(words: &[&str]) -> anyhow::Result<ic::UnclassifiedIris> {
+    let Ok(words) = <[&str; 4]>::try_from(words) else {
+        bail!("Expected 4 iris parameters, but got {}.", words.len());
+    };
+    let mut params = [0.; 4];
+    for (param, word) in params.iter_mut().zip(words) {
+        *param = parse(word, "iris parameter")?;
+    }
+    Ok(params.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prediction_to_classification;
+
+    /// Runs `script` in `repl`. Returns what the session wrote.
+    fn run(repl: &mut Repl, script: &str) -> String {
+        let mut output = Vec::new();
+        repl.run(script.as_bytes(), &mut output, None).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn commands_run_until_quit() {
+        let mut repl = Repl::new(IrisNetwork::default(), Vec::new());
+        let output = run(
+            &mut repl,
+            "help\n\nweights\n5.1, 3.5, 1.4, 0.2\nfoo\nquit\nweights\n",
+        );
+        assert!(output.starts_with(HELP));
+        assert_eq!(
+            output
+                .matches("unit 0: w = [0, 0, 0, 0], theta = 0")
+                .count(),
+            1
+        );
+        assert!(output.contains("unit 1: net = 0 -> bit 1"));
+        assert!(output.contains("output: 0b11 = 3 -> uncertain (confidence 0)"));
+        assert!(output.contains("Error: Unknown command \"foo\"."));
+    }
+
+    #[test]
+    fn thresholds_and_reject_option_change_decisions() {
+        let mut repl = Repl::new(IrisNetwork::default(), Vec::new());
+        let output = run(
+            &mut repl,
+            "theta 1 1\n5 3 1 0\ntheta 2 1\ntheta x 1\nreject 0.5\n5 3 1 0\nreject off\n5 3 1 0\n",
+        );
+        assert_eq!(repl.network().weights()[1].1, 1.);
+        assert_eq!(output.matches("output: 0b01 = 1 -> Virginica").count(), 2);
+        assert!(output.contains("Error: There are only perceptrons 0 to 1."));
+        assert!(output.contains("Error: \"x\" isn't a valid perceptron number."));
+        assert!(output.contains("-> uncertain (confidence 0)"));
+    }
+
+    #[test]
+    fn learned_irises_are_classified_as_labeled() {
+        let mut repl = Repl::new(IrisNetwork::default(), Vec::new());
+        let output = run(
+            &mut repl,
+            "learn 6 3 4.5 1.5 0\nlearn -6 -3 -4.5 -1.5 !\nlearn 1 2 3\n",
+        );
+        assert_eq!(output.matches("Learned after ").count(), 2);
+        assert!(output.contains("Error: Expected 4 iris parameters, but got 2."));
+        assert_eq!(repl.learned_irises().len(), 2);
+        let rejected: ic::UnclassifiedIris = [-6., -3., -4.5, -1.5].into();
+        assert_eq!(reject::classify(repl.network(), &rejected), None);
+        let iris: ic::UnclassifiedIris = [6., 3., 4.5, 1.5].into();
+        assert_eq!(
+            prediction_to_classification(repl.network().decide_for(iris.as_na_svec())),
+            ic::IrisSpecies::try_from(0).unwrap()
+        );
+    }
+
+    #[test]
+    fn retrained_network_is_saved_with_its_config() -> anyhow::Result<()> {
+        let training_irises = vec![(
+            [5.1, 3.5, 1.4, 0.2].into(),
+            PartialLabel::Known(ic::IrisSpecies::Setosa),
+        )];
+        let mut config = TrainingConfig::default();
+        config.preprocessing.seed = Some(3);
+        let mut repl =
+            Repl::new(IrisNetwork::default(), training_irises).with_config(config.clone());
+        let path = std::env::temp_dir().join(format!("perc_ic-repl-{}.json", std::process::id()));
+        let output = run(
+            &mut repl,
+            &format!(
+                "learn 6.3 3.3 6 2.5 Iris-virginica\nretrain\nsave {}\n",
+                path.display()
+            ),
+        );
+        assert!(output.contains("Retrained on 2 irises."), "{output}");
+        let model = TrainedModel::load(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(model.config, config);
+        assert_eq!(model.network.weights(), repl.network().weights());
+        Ok(())
+    }
+}
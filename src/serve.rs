@@ -0,0 +1,424 @@
+//! HTTP server classifying irises with a trained [`IrisNetwork`].
+//!
+//! Endpoints:
+//! * `GET /health` responds `{"status":"ok"}`.
+//! * `GET /model` responds [`ModelInfo`] of the served network.
+//! * `POST /predict` classifies one iris or a JSON array of them.
+//!   An iris is an object with fields `sepal_length`, `sepal_width`, `petal_length` and `petal_width`,
+//!   or an array of these 4 numbers. Predictions are shaped as `--format json` output with scores,
+//!   and those of a batch also carry the `index` of their iris.
+//!
+//! Errors are responded as `{"error":"..."}` with a 4xx status.
+
+use std::{io::Read, net::ToSocketAddrs, num::NonZeroUsize};
+
+use anyhow::Context;
+use tiny_http::{Header, Method};
+
+use crate::{
+    config::TrainingConfig,
+    output::{self, OutputOptions, Prediction},
+    perceptron::PerFloat,
+    reject, IrisNetwork,
+};
+
+/// Longest accepted request body, in bytes.
+pub const MAX_BODY_LEN: u64 = 1 << 20;
+
+/// Metadata of the served model, as responded by `GET /model`.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct ModelInfo {
+    /// Where the model was loaded from.
+    pub source: String,
+    pub kind: &'static str,
+    pub parameters: [&'static str; 4],
+    pub classes: [String; 3],
+    /// Confidence below which irises are classified as `null`. See [`crate::reject`].
+    pub reject_threshold: Option<PerFloat>,
+    pub units: Vec<UnitInfo>,
+    /// Config the model was trained by, if known.
+    pub config: Option<TrainingConfig>,
+}
+
+/// Weights of one perceptron of the served network.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct UnitInfo {
+    pub weights: Vec<PerFloat>,
+    pub theta: PerFloat,
+}
+
+/// Response to a request, before being sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reply {
+    pub status: u16,
+    /// JSON text.
+    pub body: String,
+}
+
+/// Iris of a `POST /predict` request.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum IrisInput {
+    Fields {
+        sepal_length: PerFloat,
+        sepal_width: PerFloat,
+        petal_length: PerFloat,
+        petal_width: PerFloat,
+    },
+    Array([PerFloat; 4]),
+}
+
+impl From<IrisInput> for ic::UnclassifiedIris {
+    fn from(input: IrisInput) -> Self {
+        match input {
+            IrisInput::Fields {
+                sepal_length,
+                sepal_width,
+                petal_length,
+                petal_width,
+            } => [sepal_length, sepal_width, petal_length, petal_width].into(),
+            IrisInput::Array(params) => params.into(),
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum PredictRequest {
+    One(IrisInput),
+    Batch(Vec<IrisInput>),
+}
+
+/// Serves an [`IrisNetwork`]. See [module docs](self).
+#[derive(Clone, Debug)]
+pub struct Server {
+    model: IrisNetwork,
+    source: String,
+    config: Option<TrainingConfig>,
+    reject_threshold: Option<PerFloat>,
+}
+
+impl Server {
+    // CRUD-C: Constructors
+
+    /// `source` tells where `model` comes from, e.g. its file path.
+    pub fn new(model: IrisNetwork, source: impl Into<String>) -> Self {
+        Self {
+            model,
+            source: source.into(),
+            config: None,
+            reject_threshold: None,
+        }
+    }
+    /// Sets the config `model` was trained by, reported by `GET /model`.
+    pub fn with_config(mut self, config: TrainingConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+    /// Makes irises less confident than `threshold` be classified as `null`.
+    pub fn with_reject_threshold(mut self, threshold: Option<PerFloat>) -> Self {
+        self.reject_threshold = threshold;
+        self
+    }
+
+    // CRUD-R: Properties
+
+    pub fn model_info(&self) -> ModelInfo {
+        use ic::IrisSpecies as S;
+        ModelInfo {
+            source: self.source.clone(),
+            kind: "OneLayerNN",
+            parameters: ["sepal_length", "sepal_width", "petal_length", "petal_width"],
+            classes: [S::Setosa, S::Versicolor, S::Virginica].map(|species| species.to_string()),
+            reject_threshold: self.reject_threshold,
+            units: self
+                .model
+                .weights()
+                .into_iter()
+                .map(|(weights, theta)| UnitInfo {
+                    weights: weights.iter().copied().collect(),
+                    theta,
+                })
+                .collect(),
+            config: self.config.clone(),
+        }
+    }
+
+    // CRUD-R: Serving
+
+    /// Answers request with `method` to `url` carrying `body`. Query of `url` is ignored.
+    pub fn handle(&self, method: &Method, url: &str, body: &[u8]) -> Reply {
+        let path = url.split_once('?').map_or(url, |(path, _)| path);
+        match (path, method) {
+            ("/health", Method::Get) => Reply::ok(&serde_json::json!({ "status": "ok" })),
+            ("/model", Method::Get) => Reply::ok(&self.model_info()),
+            ("/predict", Method::Post) => self.predict(body),
+            ("/health" | "/model" | "/predict", _) => {
+                Reply::error(405, format!("{method} isn't allowed for {path}."))
+            }
+            _ => Reply::error(404, format!("There is no {path} endpoint.")),
+        }
+    }
+    /// Listens on `addr` and answers requests with `workers` threads, until the process ends.
+    ///
+    /// Returned error reports what failed, not only why.
+    pub fn run(&self, addr: impl ToSocketAddrs, workers: NonZeroUsize) -> anyhow::Result<()> {
+        let http = bind(addr)?;
+        eprintln!(
+            "Serving \"{}\" on http://{} .",
+            self.source,
+            http.server_addr()
+        );
+        self.serve(&http, workers);
+        Ok(())
+    }
+    /// Answers requests coming to `http` with `workers` threads, until it's unblocked.
+    pub fn serve(&self, http: &tiny_http::Server, workers: NonZeroUsize) {
+        std::thread::scope(|scope| {
+            for _ in 0..workers.get() {
+                scope.spawn(|| {
+                    for request in http.incoming_requests() {
+                        self.respond(request);
+                    }
+                });
+            }
+        });
+    }
+
+    fn respond(&self, mut request: tiny_http::Request) {
+        let mut body = Vec::new();
+        let reply = match request
+            .as_reader()
+            .take(MAX_BODY_LEN + 1)
+            .read_to_end(&mut body)
+        {
+            Err(err) => Reply::error(400, format!("Failed to read request body: {err}")),
+            Ok(len) if len as u64 > MAX_BODY_LEN => Reply::error(
+                413,
+                format!("Request body is longer than {MAX_BODY_LEN} bytes."),
+            ),
+            Ok(_) => self.handle(request.method(), request.url(), &body),
+        };
+        tracing::debug!(
+            method = %request.method(),
+            url = request.url(),
+            status = reply.status,
+            "Answered request."
+        );
+        let response = tiny_http::Response::from_string(reply.body)
+            .with_status_code(reply.status)
+            .with_header(
+                Header::from_bytes("Content-Type", "application/json")
+                    .expect("Header should be valid ASCII."),
+            );
+        if let Err(err) = request.respond(response) {
+            tracing::warn!(%err, "Failed to respond to a request.");
+        }
+    }
+
+    fn predict(&self, body: &[u8]) -> Reply {
+        let value: serde_json::Value = match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(err) => return Reply::error(400, format!("Request body isn't JSON: {err}")),
+        };
+        let Ok(request) = serde_json::from_value::<PredictRequest>(value) else {
+            return Reply::error(
+                400,
+                "Expected an iris or an array of irises. An iris is an object with fields \
+                 sepal_length, sepal_width, petal_length and petal_width, or an array of 4 numbers."
+                    .to_owned(),
+            );
+        };
+        let (inputs, is_batch) = match request {
+            PredictRequest::One(input) => (vec![input], false),
+            PredictRequest::Batch(inputs) => (inputs, true),
+        };
+        let options = OutputOptions {
+            with_index: is_batch,
+            with_scores: true,
+            ..Default::default()
+        };
+        let predictions: Vec<_> = inputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, input)| {
+                output::json_object(&self.predict_one(index, input.into()), options)
+            })
+            .collect();
+        if is_batch {
+            Reply::ok(&predictions)
+        } else {
+            Reply::ok(&predictions[0])
+        }
+    }
+
+    fn predict_one(&self, index: usize, iris: ic::UnclassifiedIris) -> Prediction {
+        Prediction {
+            index,
+            iris,
+            classification: match self.reject_threshold {
+                Some(threshold) => reject::classify_or_reject(&self.model, &iris, threshold),
+                None => reject::classify(&self.model, &iris),
+            },
+            scores: Some(reject::class_scores(&self.model, &iris)),
+        }
+    }
+}
+
+/// Starts listening on `addr`, e.g. `127.0.0.1:0` for any free port, without answering yet. See [`Server::serve`].
+///
+/// Returned error reports what failed, not only why.
+pub fn bind(addr: impl ToSocketAddrs) -> anyhow::Result<tiny_http::Server> {
+    tiny_http::Server::http(addr)
+        .map_err(|err| anyhow::anyhow!(err))
+        .context("Failed to start HTTP server.")
+}
+
+impl Reply {
+    fn ok(body: &impl serde::Serialize) -> Self {
+        Self {
+            status: 200,
+            body: serde_json::to_string(body).expect("Replies should have string keys only."),
+        }
+    }
+    fn error(status: u16, message: String) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        net::{SocketAddr, TcpStream},
+    };
+
+    use super::*;
+
+    /// Serves an untrained network on a free local port with `workers` threads.
+    fn start(workers: usize) -> SocketAddr {
+        let http = bind("127.0.0.1:0").unwrap();
+        let addr = http.server_addr().to_ip().unwrap();
+        let server = Server::new(IrisNetwork::default(), "test");
+        std::thread::spawn(move || server.serve(&http, NonZeroUsize::new(workers).unwrap()));
+        addr
+    }
+
+    /// Sends request over a fresh connection. Returns status and body of the response.
+    fn request(addr: SocketAddr, method: &str, path: &str, body: &[u8]) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_owned())
+    }
+
+    fn json(body: &str) -> serde_json::Value {
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[test]
+    fn health_and_model_are_served() {
+        let addr = start(1);
+        assert_eq!(
+            request(addr, "GET", "/health", b""),
+            (200, r#"{"status":"ok"}"#.to_owned())
+        );
+
+        let (status, body) = request(addr, "GET", "/model?verbose", b"");
+        assert_eq!(status, 200);
+        let model = json(&body);
+        assert_eq!(model["source"], "test");
+        assert_eq!(model["units"].as_array().unwrap().len(), 2);
+        assert_eq!(model["config"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn one_iris_is_predicted() {
+        let addr = start(1);
+        for body in [
+            &br#"{"sepal_length":5.1,"sepal_width":3.5,"petal_length":1.4,"petal_width":0.2}"#[..],
+            b"[5.1,3.5,1.4,0.2]",
+        ] {
+            let (status, body) = request(addr, "POST", "/predict", body);
+            assert_eq!(status, 200);
+            let prediction = json(&body);
+            assert!(prediction.is_object());
+            assert_eq!(prediction["sepal_length"], 5.1);
+            // Untrained network decides the reject output.
+            assert!(prediction["classification"].is_null());
+            assert!(prediction["score_setosa"].is_number());
+            assert!(prediction.get("index").is_none());
+        }
+    }
+
+    #[test]
+    fn batch_of_irises_is_predicted_in_order() {
+        let addr = start(1);
+        let (status, body) = request(
+            addr,
+            "POST",
+            "/predict",
+            br#"[[5.1,3.5,1.4,0.2],{"sepal_length":6.3,"sepal_width":3.3,"petal_length":6.0,"petal_width":2.5}]"#,
+        );
+        assert_eq!(status, 200);
+        let predictions = json(&body);
+        let predictions = predictions.as_array().unwrap();
+        assert_eq!(predictions.len(), 2);
+        for (index, prediction) in predictions.iter().enumerate() {
+            assert_eq!(prediction["index"], index);
+        }
+        assert_eq!(predictions[1]["petal_width"], 2.5);
+    }
+
+    #[test]
+    fn bad_requests_get_errors() {
+        let addr = start(1);
+        for (method, path, body, expected_status) in [
+            ("GET", "/nowhere", &b""[..], 404),
+            ("GET", "/predict", b"", 405),
+            ("POST", "/health", b"", 405),
+            ("POST", "/predict", b"not json", 400),
+            ("POST", "/predict", br#"{"sepal_length":5.1}"#, 400),
+            ("POST", "/predict", b"[1,2,3]", 400),
+        ] {
+            let (status, body) = request(addr, method, path, body);
+            assert_eq!(status, expected_status, "{method} {path}");
+            assert!(json(&body)["error"].is_string());
+        }
+    }
+
+    #[test]
+    fn too_long_body_is_refused() {
+        let addr = start(1);
+        let body = vec![b' '; MAX_BODY_LEN as usize + 1];
+        let (status, body) = request(addr, "POST", "/predict", &body);
+        assert_eq!(status, 413);
+        assert!(json(&body)["error"].is_string());
+    }
+
+    #[test]
+    fn concurrent_requests_are_all_answered() {
+        let addr = start(4);
+        let expected = request(addr, "POST", "/predict", b"[5.1,3.5,1.4,0.2]");
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..16)
+                .map(|_| scope.spawn(|| request(addr, "POST", "/predict", b"[5.1,3.5,1.4,0.2]")))
+                .collect();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), expected);
+            }
+        });
+    }
+}
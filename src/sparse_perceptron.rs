@@ -0,0 +1,136 @@
+use crate::{
+    perceptron::PerFloat,
+    util::{BoolExpect, BoolExpectation, Correctness, SampleOutcome, SparseVec},
+    Neuron,
+};
+
+/// Perceptron taking [`SparseVec`] inputs.
+///
+/// Dot products and weight updates touch only non-zero input components.
+/// Weights grow on demand, so the input dimension doesn't have to be known upfront.
+/// Weights of components never seen in training are zeros.
+/// Accuracy and multi-sample training come from [`Neuron`].
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct SparsePerceptron {
+    wages: Vec<PerFloat>,
+    theta: PerFloat,
+}
+
+impl SparsePerceptron {
+    pub const ALPHA: PerFloat = 0.1;
+
+    // CRUD-C: Constructors
+
+    /// Creates perceptron with zero weights for `dim` input components.
+    pub fn with_dim(dim: usize) -> Self {
+        Self {
+            wages: vec![0.; dim],
+            theta: 0.,
+        }
+    }
+
+    // CRUD-R: Properties
+
+    pub fn wages(&self) -> &[PerFloat] {
+        &self.wages
+    }
+    pub fn theta(&self) -> PerFloat {
+        self.theta
+    }
+    /// Activation function.
+    pub fn activation(&self, value: PerFloat) -> bool {
+        value >= self.theta
+    }
+    /// Returns decision for given [`input`].
+    pub fn decide_for(&self, input: &SparseVec) -> bool {
+        self.activation(input.dot_dense(&self.wages))
+    }
+}
+
+impl Neuron<SparseVec> for SparsePerceptron {
+    fn decide_for(&self, input: &SparseVec) -> bool {
+        self.decide_for(input)
+    }
+    fn train_on_weighted_sample<E: BoolExpect>(
+        &mut self,
+        input: &SparseVec,
+        expectation: E,
+        weight: PerFloat,
+    ) -> SampleOutcome {
+        let BoolExpectation::Expect(expectation) = expectation.expectation() else {
+            // No expectation ==> nothing to do ==> everything is ok
+            return SampleOutcome::new(Correctness::Correct, false);
+        };
+        let prediction = self.decide_for(input);
+        if expectation == prediction {
+            // Correct, no need to improve
+            return SampleOutcome::new(Correctness::Correct, false);
+        }
+        let translation_dir = if expectation { 1. } else { -1. };
+        let translation_multiplier = translation_dir * Self::ALPHA * weight;
+        // Update self, touching only the weights of non-zero components.
+        if self.wages.len() < input.min_dim() {
+            self.wages.resize(input.min_dim(), 0.);
+        }
+        input.add_scaled_to(&mut self.wages, translation_multiplier);
+        self.theta -= translation_multiplier; // Input is -1.
+        SampleOutcome::new(Correctness::Incorrect, true) // BUT improved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_rational::Ratio;
+
+    use super::*;
+
+    /// Samples deciding `true` exactly when feature `1000` is present, among noise features.
+    fn samples() -> (Vec<SparseVec>, Vec<bool>) {
+        (0..8)
+            .map(|idx| {
+                let is_positive = idx % 2 == 0;
+                let mut input = SparseVec::new();
+                input.push(idx, 1.);
+                if is_positive {
+                    input.push(1000, 1.);
+                }
+                (input, is_positive)
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn weights_grow_to_the_highest_seen_component() {
+        let mut perceptron = SparsePerceptron::default();
+        assert!(perceptron.decide_for(&SparseVec::new()));
+        let input: SparseVec = [(2, 1.), (7, 2.)].into_iter().collect();
+        assert_eq!(
+            perceptron.train_on_sample(&input, false),
+            SampleOutcome::new(Correctness::Incorrect, true)
+        );
+        assert_eq!(perceptron.wages(), [0., 0., -0.1, 0., 0., 0., 0., -0.2]);
+        assert_eq!(perceptron.theta(), 0.1);
+        // Correct decisions leave weights alone.
+        assert_eq!(
+            perceptron.train_on_sample(&input, false),
+            SampleOutcome::new(Correctness::Correct, false)
+        );
+        assert_eq!(perceptron.wages().len(), 8);
+    }
+
+    #[test]
+    fn learns_separable_sparse_data() {
+        let (inputs, expecteds) = samples();
+        let mut perceptron = SparsePerceptron::with_dim(10);
+        let accuracy = (0..20)
+            .map(|_| perceptron.train_on(&inputs, expecteds.iter().copied()))
+            .last()
+            .flatten();
+        assert_eq!(accuracy, Some(Ratio::from_integer(1)));
+        assert_eq!(
+            perceptron.accuracy_for(&inputs, expecteds.iter().copied()),
+            Some(Ratio::from_integer(1))
+        );
+        assert!(perceptron.wages()[1000] > 0.);
+    }
+}
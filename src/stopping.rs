@@ -0,0 +1,460 @@
+//! When to stop training.
+
+use std::time::{Duration, Instant};
+
+use anyhow::ensure;
+use num_rational::Ratio;
+
+use crate::{
+    history::{ratio_to_f64, EpochRecord, TrainingHistory},
+    perceptron::PerFloat,
+};
+
+/// Limits of a training run. Training stops as soon as any of the set limits is reached.
+///
+/// Built by a constructor, which sets a limit that ends every run, followed by chained setters.
+/// Target accuracy and max updates alone don't end runs that never reach them,
+/// e.g. on data that isn't linearly separable, so every value has max epochs, max duration or patience set.
+/// Deserialization checks that too.
+///
+/// Limits are checked between epochs, so an epoch is never interrupted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "UncheckedCriteria")]
+pub struct StoppingCriteria {
+    max_epochs: Option<u64>,
+    max_duration: Option<Duration>,
+    max_updates: Option<u64>,
+    target_accuracy: Option<Ratio<u64>>,
+    patience: Option<u64>,
+}
+
+/// [`StoppingCriteria`] as deserialized, before checking that some limit ends every run.
+#[derive(serde::Deserialize)]
+struct UncheckedCriteria {
+    max_epochs: Option<u64>,
+    max_duration: Option<Duration>,
+    max_updates: Option<u64>,
+    target_accuracy: Option<Ratio<u64>>,
+    patience: Option<u64>,
+}
+
+impl TryFrom<UncheckedCriteria> for StoppingCriteria {
+    type Error = anyhow::Error;
+
+    fn try_from(unchecked: UncheckedCriteria) -> anyhow::Result<Self> {
+        let criteria = Self {
+            max_epochs: unchecked.max_epochs,
+            max_duration: unchecked.max_duration,
+            max_updates: unchecked.max_updates,
+            target_accuracy: unchecked.target_accuracy,
+            patience: unchecked.patience,
+        };
+        criteria.validate()?;
+        Ok(criteria)
+    }
+}
+
+impl StoppingCriteria {
+    // CRUD-C: Constructors
+
+    pub fn with_max_epochs(max_epochs: u64) -> Self {
+        Self::unlimited().max_epochs(max_epochs)
+    }
+    pub fn with_max_duration(max_duration: Duration) -> Self {
+        Self::unlimited().max_duration(max_duration)
+    }
+    pub fn with_patience(patience: u64) -> Self {
+        Self::unlimited().patience(patience)
+    }
+    /// Sets those of the limits that end every run which are `Some`.
+    ///
+    /// # Errors
+    /// * If all of them are `None`, so training might never end.
+    pub fn new(
+        max_epochs: Option<u64>,
+        max_duration: Option<Duration>,
+        patience: Option<u64>,
+    ) -> anyhow::Result<Self> {
+        let criteria = Self {
+            max_epochs,
+            max_duration,
+            patience,
+            ..Self::unlimited()
+        };
+        criteria.validate()?;
+        Ok(criteria)
+    }
+    /// Criteria that never stop training. Only a start for constructors, as no value may stay unlimited.
+    fn unlimited() -> Self {
+        Self {
+            max_epochs: None,
+            max_duration: None,
+            max_updates: None,
+            target_accuracy: None,
+            patience: None,
+        }
+    }
+
+    // CRUD-C: Builder setters
+
+    pub fn max_epochs(mut self, max_epochs: u64) -> Self {
+        self.max_epochs = Some(max_epochs);
+        self
+    }
+    /// Limits wall-clock time spent training.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+    /// Limits number of weight updates made while training, counted per unit.
+    ///
+    /// Rules like [`crate::perceptron::UpdateRule::Margin`] also update weights on correct decisions, which count too.
+    pub fn max_updates(mut self, max_updates: u64) -> Self {
+        self.max_updates = Some(max_updates);
+        self
+    }
+    /// Stops once an epoch reaches at least `target_accuracy`.
+    pub fn target_accuracy(mut self, target_accuracy: Ratio<u64>) -> Self {
+        self.target_accuracy = Some(target_accuracy);
+        self
+    }
+    /// Stops after more than `patience` epochs in a row without accuracy progress.
+    pub fn patience(mut self, patience: u64) -> Self {
+        self.patience = Some(patience);
+        self
+    }
+
+    // CRUD-R: Checks
+
+    /// Checks that some limit ends every run, i.e. that max epochs, max duration or patience is set.
+    ///
+    /// Patience ends runs too, as accuracy on fixed samples can't rise forever.
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.max_epochs.is_some() || self.max_duration.is_some() || self.patience.is_some(),
+            "None of max epochs, max duration and patience is set, so training might never end."
+        );
+        Ok(())
+    }
+
+    pub fn is_met_by(&self, progress: &TrainingProgress) -> bool {
+        self.met_criterion(progress).is_some()
+    }
+    /// Names the first criterion met by `progress`, e.g. `"max_epochs"`. `None` if training should go on.
+    pub fn met_criterion(&self, progress: &TrainingProgress) -> Option<&'static str> {
+        let exceeds = |limit: Option<u64>, value: u64| limit.is_some_and(|limit| value >= limit);
+        if exceeds(self.max_epochs, progress.epochs) {
+            Some("max_epochs")
+        } else if exceeds(self.max_updates, progress.updates) {
+            Some("max_updates")
+        } else if self
+            .max_duration
+            .is_some_and(|limit| progress.elapsed >= limit)
+        {
+            Some("max_duration")
+        } else if self
+            .target_accuracy
+            .is_some_and(|target| progress.last_score.is_some_and(|score| score >= target))
+        {
+            Some("target_accuracy")
+        } else if self
+            .patience
+            .is_some_and(|patience| progress.epochs_without_progress > patience)
+        {
+            Some("patience")
+        } else {
+            None
+        }
+    }
+
+    // CRUD-U: Driving training
+
+    /// Calls `train_epoch` until the criteria are met. Returns score of the last epoch.
+    ///
+    /// `train_epoch` trains for one epoch and reports what it did.
+    /// Progress is measured against `old_score`.
+    pub fn run(
+        &self,
+        old_score: Option<Ratio<u64>>,
+        train_epoch: impl FnMut() -> EpochOutcome,
+    ) -> Option<Ratio<u64>> {
+        self.run_recorded(old_score, train_epoch).last_score()
+    }
+    /// Like [`Self::run`], but returns records of all epochs.
+    pub fn run_recorded(
+        &self,
+        old_score: Option<Ratio<u64>>,
+        mut train_epoch: impl FnMut() -> EpochOutcome,
+    ) -> TrainingHistory {
+        let mut progress = TrainingProgress::new(old_score);
+        let mut history = TrainingHistory::default();
+        while !self.is_met_by(&progress) {
+            let _span = tracing::debug_span!("epoch", epoch = progress.epochs + 1).entered();
+            let started = Instant::now();
+            let outcome = train_epoch();
+            let elapsed = started.elapsed();
+            progress.record_epoch(&outcome, elapsed);
+            history.push(EpochRecord {
+                epoch: progress.epochs,
+                accuracy: outcome.score,
+                updates: outcome.updates,
+                penalty: outcome.penalty,
+                elapsed,
+            });
+        }
+        progress.log_stop(self);
+        history
+    }
+}
+
+/// What one epoch of training did, as reported to [`StoppingCriteria::run`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EpochOutcome {
+    /// Share of samples decided correctly before learning from them.
+    pub score: Option<Ratio<u64>>,
+    pub sample_count: u64,
+    /// Weight updates made, counted per unit. See [`crate::util::SampleOutcome`].
+    pub updates: u64,
+    /// Regularization penalty at the end of the epoch. See [`crate::Regularization::penalty`].
+    pub penalty: PerFloat,
+}
+
+impl EpochOutcome {
+    /// Samples decided incorrectly before learning from them.
+    pub fn mistakes(&self) -> u64 {
+        self.score.map_or(0, |score| {
+            // Exact, as the accuracy's denominator divides the sample count.
+            let correct = (score * self.sample_count).to_integer();
+            self.sample_count - correct
+        })
+    }
+}
+
+/// State of a training run, as judged by [`StoppingCriteria`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TrainingProgress {
+    epochs: u64,
+    updates: u64,
+    elapsed: Duration,
+    best_score: Option<Ratio<u64>>,
+    last_score: Option<Ratio<u64>>,
+    #[serde(default)]
+    last_penalty: PerFloat,
+    epochs_without_progress: u64,
+}
+
+impl TrainingProgress {
+    // CRUD-C: Constructors
+
+    /// Starts a run where progress means beating `old_score`.
+    pub fn new(old_score: Option<Ratio<u64>>) -> Self {
+        Self {
+            best_score: old_score,
+            ..Default::default()
+        }
+    }
+
+    // CRUD-R: Properties
+
+    /// Epochs completed so far.
+    pub fn epochs(&self) -> u64 {
+        self.epochs
+    }
+    /// Weight updates made while training so far, counted per unit.
+    pub fn updates(&self) -> u64 {
+        self.updates
+    }
+    /// Time spent in completed epochs.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+    pub fn best_score(&self) -> Option<Ratio<u64>> {
+        self.best_score
+    }
+    /// Score of the last completed epoch.
+    pub fn last_score(&self) -> Option<Ratio<u64>> {
+        self.last_score
+    }
+    /// Regularization penalty at the end of the last completed epoch.
+    pub fn last_penalty(&self) -> PerFloat {
+        self.last_penalty
+    }
+
+    // CRUD-U: Recording
+
+    /// Records an epoch that ended with `outcome` after `elapsed` time.
+    pub fn record_epoch(&mut self, outcome: &EpochOutcome, elapsed: Duration) {
+        let EpochOutcome {
+            score,
+            updates,
+            penalty,
+            ..
+        } = *outcome;
+        self.epochs += 1;
+        self.elapsed += elapsed;
+        self.updates += updates;
+        if score > self.best_score {
+            self.best_score = score;
+            self.epochs_without_progress = 0;
+        } else {
+            self.epochs_without_progress += 1;
+        }
+        self.last_score = score;
+        self.last_penalty = penalty;
+        tracing::debug!(
+            epoch = self.epochs,
+            accuracy = score.map(ratio_to_f64),
+            mistakes = outcome.mistakes(),
+            updates,
+            total_updates = self.updates,
+            penalty,
+            elapsed_ms = elapsed.as_secs_f64() * 1000.,
+            "Finished epoch."
+        );
+    }
+    /// Logs why training stopped under `criteria`.
+    pub(crate) fn log_stop(&self, criteria: &StoppingCriteria) {
+        tracing::info!(
+            criterion = criteria.met_criterion(self),
+            epochs = self.epochs,
+            updates = self.updates,
+            best_accuracy = self.best_score.map(ratio_to_f64),
+            last_accuracy = self.last_score.map(ratio_to_f64),
+            penalty = self.last_penalty,
+            "Stopped training."
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs epochs scoring `scores` in turn on 10 samples, updating 2 units per mistake.
+    /// Returns the number of epochs run, after checking they were recorded with their updates.
+    fn epochs_run(criteria: StoppingCriteria, scores: &[u64]) -> u64 {
+        let scores_run = scores.iter().cycle();
+        let mut scores = scores_run.clone();
+        let history = criteria.run_recorded(Some(Ratio::new(0, 1)), || {
+            let correct = *scores.next().unwrap();
+            EpochOutcome {
+                score: Some(Ratio::new(correct, 10)),
+                sample_count: 10,
+                updates: 2 * (10 - correct),
+                penalty: 0.,
+            }
+        });
+        for (record, &correct) in history.records().iter().zip(scores_run) {
+            assert_eq!(record.updates, 2 * (10 - correct));
+        }
+        history.records().len() as u64
+    }
+
+    #[test]
+    fn constructors_need_a_limit_that_ends_every_run() {
+        assert!(StoppingCriteria::new(Some(5), None, None).is_ok());
+        assert!(StoppingCriteria::new(None, Some(Duration::from_secs(1)), None).is_ok());
+        assert!(StoppingCriteria::new(None, None, Some(3)).is_ok());
+        assert!(StoppingCriteria::new(None, None, None).is_err());
+    }
+
+    #[test]
+    fn deserialization_rejects_criteria_that_might_never_stop() -> anyhow::Result<()> {
+        let criteria = StoppingCriteria::with_patience(3).max_updates(100);
+        let json = serde_json::to_string(&criteria)?;
+        assert_eq!(serde_json::from_str::<StoppingCriteria>(&json)?, criteria);
+
+        let unbounded = json.replace(r#""patience":3"#, r#""patience":null"#);
+        assert_ne!(unbounded, json);
+        assert!(serde_json::from_str::<StoppingCriteria>(&unbounded).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn max_epochs_stops_run() {
+        assert_eq!(epochs_run(StoppingCriteria::with_max_epochs(4), &[5]), 4);
+    }
+
+    #[test]
+    fn target_accuracy_stops_once_reached() {
+        let criteria = StoppingCriteria::with_max_epochs(100).target_accuracy(Ratio::new(8, 10));
+        assert_eq!(epochs_run(criteria, &[5, 7, 8, 9]), 3);
+    }
+
+    #[test]
+    fn max_updates_counts_weight_updates() {
+        // Each epoch makes 3 mistakes and 6 updates.
+        let criteria = StoppingCriteria::with_max_epochs(100).max_updates(13);
+        assert_eq!(epochs_run(criteria, &[7]), 3);
+    }
+
+    #[test]
+    fn patience_tolerates_epochs_without_progress() {
+        // Progress in the first epoch only, then 2 tolerated epochs and the one that stops.
+        assert_eq!(epochs_run(StoppingCriteria::with_patience(2), &[5]), 4);
+    }
+
+    #[test]
+    fn progress_tracks_best_and_last_scores() {
+        let mut progress = TrainingProgress::new(Some(Ratio::new(0, 1)));
+        for score in [Ratio::new(3, 4), Ratio::new(1, 2)] {
+            let outcome = EpochOutcome {
+                score: Some(score),
+                sample_count: 4,
+                updates: 5,
+                penalty: 0.,
+            };
+            progress.record_epoch(&outcome, Duration::ZERO);
+        }
+        assert_eq!(progress.epochs(), 2);
+        assert_eq!(progress.updates(), 5 + 5);
+        assert_eq!(progress.best_score(), Some(Ratio::new(3, 4)));
+        assert_eq!(progress.last_score(), Some(Ratio::new(1, 2)));
+    }
+
+    /// Shared buffer that JSON logs are written into.
+    #[derive(Clone, Default)]
+    struct LogCapture(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogCapture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn epochs_and_stop_are_logged() {
+        let capture = LogCapture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            epochs_run(StoppingCriteria::with_max_epochs(2), &[5]);
+        });
+
+        let logs = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<serde_json::Value> = logs
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let epochs: Vec<_> = events
+            .iter()
+            .filter(|event| event["fields"]["message"] == "Finished epoch.")
+            .collect();
+        assert_eq!(epochs.len(), 2);
+        assert_eq!(epochs[1]["fields"]["total_updates"], 20);
+        assert_eq!(epochs[1]["fields"]["mistakes"], 5);
+        assert_eq!(epochs[1]["span"]["epoch"], 2);
+        let stop = events.last().unwrap();
+        assert_eq!(stop["fields"]["message"], "Stopped training.");
+        assert_eq!(stop["fields"]["criterion"], "max_epochs");
+        assert_eq!(stop["fields"]["best_accuracy"], 0.5);
+        assert_eq!(stop["fields"]["penalty"], 0.);
+    }
+}
@@ -1,6 +1,3 @@
-pub use one_absorbing_subtractions::OneAbsorbingSubtractions;
-pub mod one_absorbing_subtractions;
-
 pub use int_expect::IntExpect;
 pub mod int_expect;
 
@@ -10,7 +7,13 @@ pub mod bool_expect;
 pub use bool_expectation::BoolExpectation;
 pub mod bool_expectation;
 
-pub use correctness::Correctness;
+pub use sparse_vec::SparseVec;
+pub mod sparse_vec;
+
+pub mod serde_array;
+pub mod serde_display;
+
+pub use correctness::{Correctness, SampleOutcome};
 pub mod correctness {
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     pub enum Correctness {
@@ -22,6 +25,29 @@ pub mod correctness {
             *self == Self::Correct
         }
     }
+
+    /// What learning from one sample did.
+    ///
+    /// Updates and mistakes differ, as e.g. [`crate::perceptron::UpdateRule::Margin`] also updates on correct decisions.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct SampleOutcome {
+        /// Whether the decision was correct before learning.
+        pub correctness: Correctness,
+        /// Number of units whose weights changed.
+        pub updates: u64,
+    }
+    impl SampleOutcome {
+        /// Outcome of a single unit.
+        pub fn new(correctness: Correctness, updated: bool) -> Self {
+            Self {
+                correctness,
+                updates: updated.into(),
+            }
+        }
+        pub fn is_correct(&self) -> bool {
+            self.correctness.is_correct()
+        }
+    }
 }
 
 /// Student's implementations of operations on `nalgebra` vectors.
@@ -14,3 +14,11 @@ impl BoolExpect for bool {
         value == *self
     }
 }
+impl<T: BoolExpect + ?Sized> BoolExpect for &T {
+    fn expectation(&self) -> BoolExpectation {
+        (**self).expectation()
+    }
+    fn is_met_by(&self, value: bool) -> bool {
+        (**self).is_met_by(value)
+    }
+}
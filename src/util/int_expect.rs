@@ -5,6 +5,15 @@ pub trait IntExpect {
     type BoolExpectation: crate::util::BoolExpect;
     fn is_met_by(&self, value: Self::ProvidedInt) -> bool;
     fn bit_expectation(&self, idx: usize) -> Self::BoolExpectation;
+    /// Output reserved for samples of none of the classes, see [`crate::PartialLabel::Reject`].
+    ///
+    /// All units off by default. Override it where that output means a class.
+    fn reject_output() -> Self::ProvidedInt
+    where
+        Self: Sized,
+    {
+        num_traits::zero()
+    }
 }
 impl<I: PrimInt> IntExpect for I {
     type ProvidedInt = I;
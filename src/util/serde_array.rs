@@ -0,0 +1,26 @@
+//! (De)serializes arrays of any const generic length as sequences.
+//!
+//! Serde itself supports only arrays of lengths up to 32 written out explicitly.
+//! Use with `#[serde(with = "crate::util::serde_array")]`.
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<S, T, const N: usize>(array: &[T; N], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    serializer.collect_seq(array)
+}
+
+pub fn deserialize<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let elements = Vec::<T>::deserialize(deserializer)?;
+    let len = elements.len();
+    elements
+        .try_into()
+        .map_err(|_| D::Error::invalid_length(len, &format!("an array of length {N}").as_str()))
+}
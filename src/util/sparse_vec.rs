@@ -0,0 +1,147 @@
+use crate::perceptron::{PerFloat, PerVec};
+
+/// Vector that stores only its non-zero components.
+///
+/// Suits inputs with thousands of dimensions but few non-zero components, like bag-of-words features.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SparseVec {
+    // Invariant: Indices are strictly ascending and every value is non-zero.
+    indices: Vec<usize>,
+    values: Vec<PerFloat>,
+}
+
+impl SparseVec {
+    // CRUD-C: Constructors
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // CRUD-R: Properties
+
+    /// Number of stored (non-zero) components.
+    pub fn nnz(&self) -> usize {
+        self.indices.len()
+    }
+    /// Smallest dimension that can hold this vector.
+    pub fn min_dim(&self) -> usize {
+        self.indices.last().map_or(0, |last| last + 1)
+    }
+    /// Iterates over `(index, value)` of non-zero components in ascending index order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, PerFloat)> + '_ {
+        self.indices
+            .iter()
+            .copied()
+            .zip(self.values.iter().copied())
+    }
+    pub fn get(&self, index: usize) -> PerFloat {
+        self.indices
+            .binary_search(&index)
+            .map_or(0., |pos| self.values[pos])
+    }
+    pub fn norm_squared(&self) -> PerFloat {
+        self.values.iter().map(|v| v * v).sum()
+    }
+
+    // CRUD-R: Operations with dense vectors
+
+    /// Dot product with a dense vector. Components beyond `dense.len()` are treated as zeros.
+    pub fn dot_dense(&self, dense: &[PerFloat]) -> PerFloat {
+        self.iter()
+            .take_while(|&(idx, _)| idx < dense.len())
+            .map(|(idx, value)| value * dense[idx])
+            .sum()
+    }
+    /// Performs `dense += factor * self`.
+    ///
+    /// # Panics
+    /// * If `dense` is shorter than [`Self::min_dim`].
+    pub fn add_scaled_to(&self, dense: &mut [PerFloat], factor: PerFloat) {
+        for (idx, value) in self.iter() {
+            dense[idx] += factor * value;
+        }
+    }
+
+    // CRUD-U: Modifiers
+
+    /// Appends a component.
+    ///
+    /// Zero values are skipped.
+    ///
+    /// # Panics
+    /// * If `index` isn't greater than every already stored index.
+    pub fn push(&mut self, index: usize, value: PerFloat) {
+        if let Some(&last) = self.indices.last() {
+            assert!(
+                last < index,
+                "Indices of `SparseVec` must be pushed in strictly ascending order."
+            );
+        }
+        if value != 0. {
+            self.indices.push(index);
+            self.values.push(value);
+        }
+    }
+}
+
+/// Collects components in any order. Values at repeated indices are summed.
+impl FromIterator<(usize, PerFloat)> for SparseVec {
+    fn from_iter<T: IntoIterator<Item = (usize, PerFloat)>>(iter: T) -> Self {
+        let mut pairs: Vec<_> = iter.into_iter().collect();
+        pairs.sort_unstable_by_key(|&(idx, _)| idx);
+        let mut merged: Vec<(usize, PerFloat)> = Vec::with_capacity(pairs.len());
+        for (idx, value) in pairs {
+            match merged.last_mut() {
+                Some((last_idx, last_value)) if *last_idx == idx => *last_value += value,
+                _ => merged.push((idx, value)),
+            }
+        }
+        let (indices, values) = merged.into_iter().filter(|&(_, v)| v != 0.).unzip();
+        Self { indices, values }
+    }
+}
+
+// CRUD-C: Conversions
+impl<const D: usize> From<&PerVec<D>> for SparseVec {
+    fn from(dense: &PerVec<D>) -> Self {
+        dense.iter().copied().enumerate().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collecting_sorts_merges_and_drops_zeros() {
+        let vec: SparseVec = [(4, 1.), (1, 2.), (4, -1.), (2, 0.), (1, 0.5)]
+            .into_iter()
+            .collect();
+        assert_eq!(vec.iter().collect::<Vec<_>>(), [(1, 2.5)]);
+        assert_eq!(vec.nnz(), 1);
+        assert_eq!(vec.min_dim(), 2);
+        assert_eq!(vec.get(4), 0.);
+    }
+
+    #[test]
+    fn operates_with_dense_vectors() {
+        let sparse = SparseVec::from(&PerVec::<4>::new(1., 0., 0., 3.));
+        assert_eq!(sparse.nnz(), 2);
+        assert_eq!(sparse.norm_squared(), 10.);
+        assert_eq!(sparse.dot_dense(&[2., 5., 5., 1.]), 5.);
+        // Components beyond the dense vector count as zeros.
+        assert_eq!(sparse.dot_dense(&[2.]), 2.);
+
+        let mut dense = [1.; 4];
+        sparse.add_scaled_to(&mut dense, -2.);
+        assert_eq!(dense, [-1., 1., 1., -5.]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pushing_out_of_order_panics() {
+        let mut vec = SparseVec::new();
+        vec.push(3, 1.);
+        vec.push(3, 1.);
+    }
+}